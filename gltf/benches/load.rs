@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glam::{Vec2, Vec3};
+use gltf::{BufferView, Glb, GlbRef, Mesh, MeshPrimitive, Mode};
+
+// A grid of about a million vertices, written out as a GLB
+fn grid(size: u32) -> Vec<u8> {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for y in 0..size {
+        for x in 0..size {
+            let uv = Vec2::new(x as f32, y as f32) / (size - 1) as f32;
+            positions.push(Vec3::new(
+                uv.x,
+                (uv.x * 10.0).sin() * (uv.y * 10.0).cos(),
+                uv.y,
+            ));
+            normals.push(Vec3::Y);
+            uvs.push(uv);
+        }
+    }
+    let mut indices = Vec::new();
+    for y in 0..size - 1 {
+        for x in 0..size - 1 {
+            let i = y * size + x;
+            indices.extend_from_slice(&[i, i + size, i + 1, i + 1, i + size, i + size + 1]);
+        }
+    }
+
+    let mut glb = Glb::new();
+    let attributes = HashMap::from([
+        (
+            "POSITION".to_owned(),
+            glb.push_accessor(&positions, Some(BufferView::ARRAY_BUFFER)),
+        ),
+        (
+            "NORMAL".to_owned(),
+            glb.push_accessor(&normals, Some(BufferView::ARRAY_BUFFER)),
+        ),
+        (
+            "TEXCOORD_0".to_owned(),
+            glb.push_accessor(&uvs, Some(BufferView::ARRAY_BUFFER)),
+        ),
+    ]);
+    let indices = glb.push_indices(&indices);
+    glb.gltf.meshes.push(Mesh {
+        primitives: vec![MeshPrimitive {
+            attributes,
+            indices: Some(indices),
+            material: None,
+            mode: Mode::Triangles,
+            targets: Vec::new(),
+        }],
+        weights: None,
+        name: None,
+        extras: Default::default(),
+    });
+    glb.to_bytes().unwrap()
+}
+
+fn load(c: &mut Criterion) {
+    let bytes = grid(1024);
+
+    c.bench_function("Glb::load", |b| {
+        b.iter(|| {
+            let glb = Glb::load(black_box(&bytes)).unwrap();
+            let primitive = &glb.gltf.meshes[0].primitives[0];
+            let positions = primitive
+                .attribute(&glb, "POSITION")
+                .unwrap()
+                .unwrap()
+                .read_vec3_f32(&glb)
+                .unwrap();
+            let indices = primitive.get_indices_data(&glb).unwrap().unwrap();
+            black_box((positions, indices));
+        })
+    });
+
+    c.bench_function("GlbRef::load", |b| {
+        b.iter(|| {
+            let glb = GlbRef::load(black_box(&bytes)).unwrap();
+            let primitive = &glb.gltf.meshes[0].primitives[0];
+            let positions = primitive
+                .attribute(&glb, "POSITION")
+                .unwrap()
+                .unwrap()
+                .view::<Vec3>(&glb)
+                .unwrap();
+            let positions = positions.as_slice().unwrap();
+            let indices = glb.gltf.accessors[primitive.indices.unwrap()]
+                .view::<u32>(&glb)
+                .unwrap();
+            black_box((positions, indices.iter().max()));
+        })
+    });
+}
+
+criterion_group!(benches, load);
+criterion_main!(benches);
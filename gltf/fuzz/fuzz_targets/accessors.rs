@@ -0,0 +1,36 @@
+#![no_main]
+
+use gltf::Glb;
+use libfuzzer_sys::fuzz_target;
+
+// Wraps arbitrary JSON and binary data in a well formed GLB, so the fuzzer
+// spends its time on the accessors rather than the header
+fuzz_target!(|input: (&str, &[u8])| {
+    let (json, bin) = input;
+    let mut bytes = Vec::new();
+    let length = 12 + 8 + json.len() + 8 + bin.len();
+    bytes.extend_from_slice(&0x46546C67u32.to_le_bytes());
+    bytes.extend_from_slice(&2u32.to_le_bytes());
+    bytes.extend_from_slice(&(length as u32).to_le_bytes());
+    bytes.extend_from_slice(&(json.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&0x4E4F534Au32.to_le_bytes());
+    bytes.extend_from_slice(json.as_bytes());
+    bytes.extend_from_slice(&(bin.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&0x004E4942u32.to_le_bytes());
+    bytes.extend_from_slice(bin);
+
+    let Ok(glb) = Glb::load(&bytes) else {
+        return;
+    };
+
+    let _ = gltf::validate(&glb);
+    for accessor in &glb.gltf.accessors {
+        let _ = accessor.get_data(&glb);
+    }
+    for primitive in glb.gltf.meshes.iter().flat_map(|mesh| &mesh.primitives) {
+        for attribute in primitive.attributes.keys() {
+            let _ = primitive.get_attribute_data(&glb, attribute);
+        }
+        let _ = primitive.get_indices_data(&glb);
+    }
+});
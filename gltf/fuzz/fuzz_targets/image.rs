@@ -0,0 +1,8 @@
+#![no_main]
+
+use gltf::Pixels;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = Pixels::decode(data);
+});
@@ -0,0 +1,617 @@
+use std::borrow::Cow;
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::{slice, Accessor, Buffers, ComponentType, Error, Result, MAX_BUFFER_SIZE};
+
+fn decode<const N: usize>(data: &[u8], f: impl Fn([u8; N]) -> f32) -> Vec<f32> {
+    data.chunks_exact(N)
+        .map(|bytes| f(bytes.try_into().unwrap()))
+        .collect()
+}
+
+fn decode_indices(ty: ComponentType, data: &[u8]) -> Result<Vec<u32>> {
+    Ok(match ty {
+        ComponentType::U8 => data.iter().map(|&byte| byte as u32).collect(),
+        ComponentType::U16 => data
+            .chunks_exact(2)
+            .map(|short| u16::from_le_bytes([short[0], short[1]]) as u32)
+            .collect(),
+        ComponentType::U32 => data
+            .chunks_exact(4)
+            .map(|int| u32::from_le_bytes([int[0], int[1], int[2], int[3]]))
+            .collect(),
+        ty => return Err(Error::ComponentType(ty)),
+    })
+}
+
+impl Accessor {
+    pub fn components(&self) -> Result<usize> {
+        match self.element_type.as_str() {
+            "SCALAR" => Ok(1),
+            "VEC2" => Ok(2),
+            "VEC3" => Ok(3),
+            "VEC4" | "MAT2" => Ok(4),
+            "MAT3" => Ok(9),
+            "MAT4" => Ok(16),
+            _ => Err(Error::ElementType(self.element_type.clone())),
+        }
+    }
+
+    // Matrix columns start on 4 byte boundaries, so small component types
+    // leave padding between them
+    fn layout(&self) -> Result<(usize, usize, usize)> {
+        let size = self.component_type.size_of();
+        let (columns, rows) = match self.element_type.as_str() {
+            "MAT2" => (2, 2),
+            "MAT3" => (3, 3),
+            "MAT4" => (4, 4),
+            _ => (1, self.components()?),
+        };
+        let column_stride = if columns > 1 {
+            (rows * size).next_multiple_of(4)
+        } else {
+            rows * size
+        };
+        Ok((columns, rows, column_stride))
+    }
+
+    /// Size of an element in the buffer, including any matrix padding.
+    pub fn element_size(&self) -> Result<usize> {
+        let (columns, _, column_stride) = self.layout()?;
+        Ok(columns * column_stride)
+    }
+
+    // Takes `count` elements starting every `stride` bytes out of `data`,
+    // dropping any matrix padding, and only copying if there is any
+    fn unpack<'a>(&self, data: &'a [u8], stride: usize) -> Result<Cow<'a, [u8]>> {
+        let size = self.component_type.size_of();
+        let (columns, rows, column_stride) = self.layout()?;
+        let element_size = columns * column_stride;
+        if self.count == 0 {
+            return Ok(Cow::Borrowed(&[]));
+        }
+
+        let length = stride
+            .checked_mul(self.count - 1)
+            .and_then(|length| length.checked_add(element_size))
+            .ok_or(Error::Bounds {
+                start: 0,
+                end: usize::MAX,
+                length: data.len(),
+            })?;
+        let data = slice(data, 0, length)?;
+
+        if stride == element_size && column_stride == rows * size {
+            return Ok(Cow::Borrowed(data));
+        }
+
+        let mut packed = Vec::with_capacity(self.count * columns * rows * size);
+        for element in 0..self.count {
+            let element = &data[element * stride..];
+            for column in 0..columns {
+                let column = &element[column * column_stride..];
+                packed.extend_from_slice(&column[..rows * size]);
+            }
+        }
+        Ok(Cow::Owned(packed))
+    }
+
+    fn read_dense<'a>(&self, glb: &'a impl Buffers) -> Result<Cow<'a, [u8]>> {
+        let Some(buffer_view) = self.buffer_view else {
+            // Accessors without a buffer view are all zeros, usually with sparse values on top
+            // The count comes straight from the JSON, so it's capped like a
+            // buffer allocated from it would be
+            let length =
+                (self.component_type.size_of() * self.components()?).saturating_mul(self.count);
+            if length > MAX_BUFFER_SIZE {
+                return Err(Error::TooLarge {
+                    size: length,
+                    max: MAX_BUFFER_SIZE,
+                });
+            }
+            let zeros = vec![0; length];
+            return Ok(Cow::Owned(zeros));
+        };
+
+        let (buffer_view, view) = glb.buffer_view(buffer_view)?;
+        let stride = match buffer_view.byte_stride {
+            0 => self.element_size()?,
+            stride => stride,
+        };
+        let data = view.get(self.byte_offset..).ok_or(Error::Bounds {
+            start: self.byte_offset,
+            end: view.len(),
+            length: view.len(),
+        })?;
+        self.unpack(data, stride)
+    }
+
+    /// The accessor's components packed tightly together, without the
+    /// buffer view's stride or any matrix padding, and with sparse values
+    /// already applied.
+    pub fn get_data(&self, glb: &impl Buffers) -> Result<Vec<u8>> {
+        Ok(self.data(glb)?.into_owned())
+    }
+
+    /// Like `get_data`, but borrows the buffer when it's already tightly
+    /// packed, which is usually the case for anything but sparse accessors.
+    pub fn data<'a>(&self, glb: &'a impl Buffers) -> Result<Cow<'a, [u8]>> {
+        let mut data = self.read_dense(glb)?;
+        let Some(sparse) = &self.sparse else {
+            return Ok(data);
+        };
+
+        let (_, view) = glb.buffer_view(sparse.indices.buffer_view)?;
+        let indices = slice(
+            view,
+            sparse.indices.byte_offset,
+            sparse
+                .count
+                .saturating_mul(sparse.indices.component_type.size_of()),
+        )?;
+        let indices = decode_indices(sparse.indices.component_type, indices)?;
+
+        let (_, view) = glb.buffer_view(sparse.values.buffer_view)?;
+        let values = view.get(sparse.values.byte_offset..).ok_or(Error::Bounds {
+            start: sparse.values.byte_offset,
+            end: view.len(),
+            length: view.len(),
+        })?;
+        let values = Accessor {
+            count: sparse.count,
+            buffer_view: None,
+            byte_offset: 0,
+            component_type: self.component_type,
+            normalized: self.normalized,
+            element_type: self.element_type.clone(),
+            max: None,
+            min: None,
+            sparse: None,
+        }
+        .unpack(values, self.element_size()?)?;
+
+        let packed_size = self.component_type.size_of() * self.components()?;
+        for (index, value) in indices.into_iter().zip(values.chunks_exact(packed_size)) {
+            let start = index as usize * packed_size;
+            data.to_mut()
+                .get_mut(start..start + packed_size)
+                .ok_or(Error::Index {
+                    kind: "element",
+                    index: index as usize,
+                })?
+                .copy_from_slice(value);
+        }
+        Ok(data)
+    }
+
+    fn expect(&self, expected: &'static [&'static str]) -> Result<()> {
+        if expected.contains(&self.element_type.as_str()) {
+            Ok(())
+        } else {
+            Err(Error::UnexpectedType {
+                expected,
+                found: self.element_type.clone(),
+            })
+        }
+    }
+
+    /// Every component as a float, with normalized integers mapped to
+    /// `[0, 1]` or `[-1, 1]`.
+    pub fn read_floats(&self, glb: &impl Buffers) -> Result<Vec<f32>> {
+        let data = self.data(glb)?;
+        Ok(match (self.component_type, self.normalized) {
+            (ComponentType::F32, _) => decode(&data, f32::from_le_bytes),
+            (ComponentType::U8, true) => decode(&data, |[x]| x as f32 / 255.0),
+            (ComponentType::U8, false) => decode(&data, |[x]| x as f32),
+            (ComponentType::I8, true) => decode(&data, |[x]| (x as i8 as f32 / 127.0).max(-1.0)),
+            (ComponentType::I8, false) => decode(&data, |[x]| x as i8 as f32),
+            (ComponentType::U16, true) => decode(&data, |x| u16::from_le_bytes(x) as f32 / 65535.0),
+            (ComponentType::U16, false) => decode(&data, |x| u16::from_le_bytes(x) as f32),
+            (ComponentType::I16, true) => decode(&data, |x| {
+                (i16::from_le_bytes(x) as f32 / 32767.0).max(-1.0)
+            }),
+            (ComponentType::I16, false) => decode(&data, |x| i16::from_le_bytes(x) as f32),
+            (ComponentType::U32, false) => decode(&data, |x| u32::from_le_bytes(x) as f32),
+            (ty @ ComponentType::U32, true) => return Err(Error::ComponentType(ty)),
+        })
+    }
+
+    pub fn read_scalar(&self, glb: &impl Buffers) -> Result<Vec<f32>> {
+        self.expect(&["SCALAR"])?;
+        self.read_floats(glb)
+    }
+
+    pub fn read_vec2(&self, glb: &impl Buffers) -> Result<Vec<Vec2>> {
+        self.expect(&["VEC2"])?;
+        Ok(self
+            .read_floats(glb)?
+            .chunks_exact(2)
+            .map(Vec2::from_slice)
+            .collect())
+    }
+
+    pub fn read_vec3_f32(&self, glb: &impl Buffers) -> Result<Vec<Vec3>> {
+        self.expect(&["VEC3"])?;
+        Ok(self
+            .read_floats(glb)?
+            .chunks_exact(3)
+            .map(Vec3::from_slice)
+            .collect())
+    }
+
+    pub fn read_vec4(&self, glb: &impl Buffers) -> Result<Vec<Vec4>> {
+        self.expect(&["VEC4"])?;
+        Ok(self
+            .read_floats(glb)?
+            .chunks_exact(4)
+            .map(Vec4::from_slice)
+            .collect())
+    }
+
+    /// Vertex colours as RGBA, with an alpha of 1 for RGB colours.
+    pub fn read_colors(&self, glb: &impl Buffers) -> Result<Vec<Vec4>> {
+        self.expect(&["VEC3", "VEC4"])?;
+        let floats = self.read_floats(glb)?;
+        Ok(if self.element_type == "VEC3" {
+            floats
+                .chunks_exact(3)
+                .map(|rgb| Vec3::from_slice(rgb).extend(1.0))
+                .collect()
+        } else {
+            floats.chunks_exact(4).map(Vec4::from_slice).collect()
+        })
+    }
+
+    pub fn read_mat4(&self, glb: &impl Buffers) -> Result<Vec<Mat4>> {
+        self.expect(&["MAT4"])?;
+        Ok(self
+            .read_floats(glb)?
+            .chunks_exact(16)
+            .map(Mat4::from_cols_slice)
+            .collect())
+    }
+
+    /// `JOINTS_n` attributes, which index into a skin's joints.
+    pub fn read_joints(&self, glb: &impl Buffers) -> Result<Vec<[u16; 4]>> {
+        self.expect(&["VEC4"])?;
+        if self.normalized {
+            return Err(Error::ComponentType(self.component_type));
+        }
+        let joints: Vec<u16> = match self.component_type {
+            ComponentType::U8 => self.data(glb)?.iter().map(|x| *x as u16).collect(),
+            ComponentType::U16 => self
+                .data(glb)?
+                .chunks_exact(2)
+                .map(|x| u16::from_le_bytes([x[0], x[1]]))
+                .collect(),
+            ty => return Err(Error::ComponentType(ty)),
+        };
+        Ok(joints
+            .chunks_exact(4)
+            .map(|x| [x[0], x[1], x[2], x[3]])
+            .collect())
+    }
+
+    pub fn read_indices(&self, glb: &impl Buffers) -> Result<Vec<u32>> {
+        self.expect(&["SCALAR"])?;
+        decode_indices(self.component_type, &self.data(glb)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{fixture, Glb};
+
+    // One buffer view over the whole buffer, with an accessor for each of
+    // `accessors` reading from the start of it
+    fn view(accessors: &[Value], bin: &[u8]) -> Glb {
+        let accessors = accessors
+            .iter()
+            .map(|accessor| {
+                let mut accessor = accessor.clone();
+                accessor["bufferView"] = json!(0);
+                accessor
+            })
+            .collect();
+        fixture::raw(
+            json!([{"buffer": 0, "byteLength": bin.len()}]),
+            Value::Array(accessors),
+            bin.to_vec(),
+        )
+    }
+
+    fn accessor(glb: &Glb, index: usize) -> &Accessor {
+        &glb.gltf.accessors[index]
+    }
+
+    #[test]
+    fn normalized_integers() {
+        let glb = view(
+            &[json!({"componentType": 5121, "normalized": true, "count": 4, "type": "SCALAR"})],
+            &[0, 255, 51, 0],
+        );
+        assert_eq!(
+            accessor(&glb, 0).read_scalar(&glb).unwrap(),
+            [0.0, 1.0, 0.2, 0.0]
+        );
+
+        let glb = view(
+            &[json!({"componentType": 5120, "normalized": true, "count": 4, "type": "SCALAR"})],
+            &[128, 129, 0, 127],
+        );
+        assert_eq!(
+            accessor(&glb, 0).read_scalar(&glb).unwrap(),
+            [-1.0, -1.0, 0.0, 1.0]
+        );
+
+        let bin: Vec<u8> = [65535u16, 0]
+            .iter()
+            .chain(&[0x8000, 0x7fff])
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let glb = view(
+            &[
+                json!({"componentType": 5123, "normalized": true, "count": 2, "type": "SCALAR"}),
+                json!({"byteOffset": 4, "componentType": 5122, "normalized": true, "count": 2, "type": "SCALAR"}),
+            ],
+            &bin,
+        );
+        assert_eq!(accessor(&glb, 0).read_scalar(&glb).unwrap(), [1.0, 0.0]);
+        assert_eq!(accessor(&glb, 1).read_scalar(&glb).unwrap(), [-1.0, 1.0]);
+
+        let glb = view(
+            &[json!({"componentType": 5125, "normalized": true, "count": 1, "type": "SCALAR"})],
+            &[0; 4],
+        );
+        assert!(matches!(
+            accessor(&glb, 0).read_scalar(&glb),
+            Err(Error::ComponentType(ComponentType::U32))
+        ));
+    }
+
+    #[test]
+    fn integers() {
+        let glb = view(
+            &[
+                json!({"componentType": 5121, "count": 4, "type": "SCALAR"}),
+                json!({"componentType": 5120, "count": 4, "type": "SCALAR"}),
+                json!({"componentType": 5123, "count": 2, "type": "SCALAR"}),
+                json!({"componentType": 5125, "count": 1, "type": "SCALAR"}),
+                json!({"componentType": 5121, "count": 1, "type": "VEC4"}),
+                json!({"componentType": 5123, "count": 1, "type": "VEC2"}),
+            ],
+            &[1, 2, 255, 128],
+        );
+        assert_eq!(
+            accessor(&glb, 0).read_scalar(&glb).unwrap(),
+            [1.0, 2.0, 255.0, 128.0]
+        );
+        assert_eq!(
+            accessor(&glb, 1).read_scalar(&glb).unwrap(),
+            [1.0, 2.0, -1.0, -128.0]
+        );
+        assert_eq!(
+            accessor(&glb, 0).read_indices(&glb).unwrap(),
+            [1, 2, 255, 128]
+        );
+        assert_eq!(
+            accessor(&glb, 2).read_indices(&glb).unwrap(),
+            [0x0201, 0x80ff]
+        );
+        assert_eq!(accessor(&glb, 3).read_indices(&glb).unwrap(), [0x80ff0201]);
+        assert_eq!(
+            accessor(&glb, 4).read_joints(&glb).unwrap(),
+            [[1, 2, 255, 128]]
+        );
+        assert_eq!(
+            accessor(&glb, 5).read_vec2(&glb).unwrap(),
+            [Vec2::new(513.0, 33023.0)]
+        );
+
+        assert!(matches!(
+            accessor(&glb, 1).read_indices(&glb),
+            Err(Error::ComponentType(ComponentType::I8))
+        ));
+        assert!(matches!(
+            accessor(&glb, 4).read_vec3_f32(&glb),
+            Err(Error::UnexpectedType { found, .. }) if found == "VEC4"
+        ));
+    }
+
+    #[test]
+    fn strides_and_offsets() {
+        // Positions and normals interleaved, with the normals read from
+        // 12 bytes in
+        let floats: Vec<f32> = (0..12).map(|x| x as f32).collect();
+        let glb = fixture::raw(
+            json!([{"buffer": 0, "byteLength": 48, "byteStride": 24}]),
+            json!([
+                {"bufferView": 0, "componentType": 5126, "count": 2, "type": "VEC3"},
+                {"bufferView": 0, "byteOffset": 12, "componentType": 5126, "count": 2, "type": "VEC3"},
+            ]),
+            bytemuck::cast_slice(&floats).to_vec(),
+        );
+        assert_eq!(
+            accessor(&glb, 0).read_vec3_f32(&glb).unwrap(),
+            [Vec3::new(0.0, 1.0, 2.0), Vec3::new(6.0, 7.0, 8.0)]
+        );
+        assert_eq!(
+            accessor(&glb, 1).read_vec3_f32(&glb).unwrap(),
+            [Vec3::new(3.0, 4.0, 5.0), Vec3::new(9.0, 10.0, 11.0)]
+        );
+        assert!(matches!(
+            accessor(&glb, 0).data(&glb).unwrap(),
+            Cow::Owned(_)
+        ));
+
+        // Reading past the end of the view
+        let glb = view(
+            &[json!({"componentType": 5126, "count": 3, "type": "VEC2"})],
+            &[0; 20],
+        );
+        assert!(matches!(
+            accessor(&glb, 0).read_vec2(&glb),
+            Err(Error::Bounds { .. })
+        ));
+    }
+
+    #[test]
+    fn matrix_padding() {
+        // Columns of byte matrices start on 4 byte boundaries
+        let glb = view(
+            &[
+                json!({"componentType": 5121, "count": 1, "type": "MAT2"}),
+                json!({"componentType": 5121, "count": 1, "type": "MAT3"}),
+            ],
+            &[1, 2, 0, 0, 3, 4, 0, 0, 5, 6, 7, 0],
+        );
+        assert_eq!(accessor(&glb, 0).element_size().unwrap(), 8);
+        assert_eq!(
+            accessor(&glb, 0).read_floats(&glb).unwrap(),
+            [1.0, 2.0, 3.0, 4.0]
+        );
+        assert_eq!(accessor(&glb, 1).element_size().unwrap(), 12);
+        assert_eq!(
+            accessor(&glb, 1).read_floats(&glb).unwrap(),
+            [1.0, 2.0, 0.0, 3.0, 4.0, 0.0, 5.0, 6.0, 7.0]
+        );
+
+        let matrix = Mat4::from_cols_array(&std::array::from_fn(|i| i as f32));
+        let glb = view(
+            &[json!({"componentType": 5126, "count": 1, "type": "MAT4"})],
+            bytemuck::bytes_of(&matrix),
+        );
+        assert_eq!(accessor(&glb, 0).read_mat4(&glb).unwrap(), [matrix]);
+    }
+
+    #[test]
+    fn sparse_values_replace_dense_ones() {
+        // Dense floats, then u8 sparse indices padded to 4 bytes, then the
+        // values they replace
+        let mut bin: Vec<u8> = bytemuck::cast_slice(&[0.0f32, 1.0, 2.0, 3.0]).to_vec();
+        bin.extend_from_slice(&[3, 1, 0, 0]);
+        bin.extend_from_slice(bytemuck::cast_slice(&[30.0f32, 10.0]));
+        let glb = fixture::raw(
+            json!([
+                {"buffer": 0, "byteLength": 16},
+                {"buffer": 0, "byteOffset": 16, "byteLength": 4},
+                {"buffer": 0, "byteOffset": 20, "byteLength": 8},
+            ]),
+            json!([
+                {"bufferView": 0, "componentType": 5126, "count": 4, "type": "SCALAR", "sparse": {
+                    "count": 2,
+                    "indices": {"bufferView": 1, "componentType": 5121},
+                    "values": {"bufferView": 2},
+                }},
+                {"bufferView": 0, "componentType": 5126, "count": 4, "type": "SCALAR", "sparse": {
+                    "count": 1,
+                    "indices": {"bufferView": 1, "componentType": 5121},
+                    "values": {"bufferView": 2, "byteOffset": 4},
+                }},
+            ]),
+            bin,
+        );
+        assert_eq!(
+            accessor(&glb, 0).read_scalar(&glb).unwrap(),
+            [0.0, 10.0, 2.0, 30.0]
+        );
+        assert_eq!(
+            accessor(&glb, 1).read_scalar(&glb).unwrap(),
+            [0.0, 1.0, 2.0, 10.0]
+        );
+        // The buffer itself is left alone
+        assert_eq!(
+            bytemuck::cast_slice::<u8, f32>(&glb.buffers[0][..16]),
+            [0.0, 1.0, 2.0, 3.0]
+        );
+    }
+
+    #[test]
+    fn sparse_values_without_a_buffer_view() {
+        let mut bin: Vec<u8> = [2u16, 0].iter().flat_map(|x| x.to_le_bytes()).collect();
+        bin.extend_from_slice(&[255, 0, 51, 0]);
+        let glb = fixture::raw(
+            json!([
+                {"buffer": 0, "byteLength": 4},
+                {"buffer": 0, "byteOffset": 4, "byteLength": 4},
+            ]),
+            json!([
+                {"componentType": 5121, "normalized": true, "count": 3, "type": "VEC2", "sparse": {
+                    "count": 2,
+                    "indices": {"bufferView": 0, "componentType": 5123},
+                    "values": {"bufferView": 1},
+                }},
+                {"componentType": 5126, "count": 2, "type": "VEC3"},
+            ]),
+            bin,
+        );
+        assert_eq!(
+            accessor(&glb, 0).read_vec2(&glb).unwrap(),
+            [Vec2::new(0.2, 0.0), Vec2::ZERO, Vec2::new(1.0, 0.0)]
+        );
+        assert_eq!(
+            accessor(&glb, 1).read_vec3_f32(&glb).unwrap(),
+            [Vec3::ZERO; 2]
+        );
+    }
+
+    #[test]
+    fn sparse_indices_out_of_range() {
+        let glb = fixture::raw(
+            json!([
+                {"buffer": 0, "byteLength": 4},
+                {"buffer": 0, "byteOffset": 4, "byteLength": 4},
+            ]),
+            json!([
+                {"componentType": 5126, "count": 2, "type": "SCALAR", "sparse": {
+                    "count": 1,
+                    "indices": {"bufferView": 0, "componentType": 5125},
+                    "values": {"bufferView": 1},
+                }},
+                {"componentType": 5126, "count": 2, "type": "SCALAR", "sparse": {
+                    "count": 2,
+                    "indices": {"bufferView": 0, "componentType": 5125},
+                    "values": {"bufferView": 1},
+                }},
+            ]),
+            [2, 0, 0, 0, 0, 0, 128, 63].to_vec(),
+        );
+        assert!(matches!(
+            accessor(&glb, 0).read_scalar(&glb),
+            Err(Error::Index {
+                kind: "element",
+                index: 2
+            })
+        ));
+        // More sparse values than the views hold
+        assert!(matches!(
+            accessor(&glb, 1).read_scalar(&glb),
+            Err(Error::Bounds { .. })
+        ));
+    }
+
+    #[test]
+    fn oversized_zeros() {
+        let glb = fixture::raw(
+            json!([]),
+            json!([
+                {"componentType": 5126, "count": MAX_BUFFER_SIZE / 12 + 1, "type": "VEC3"},
+                {"componentType": 5126, "count": usize::MAX, "type": "VEC3"},
+            ]),
+            Vec::new(),
+        );
+        for index in 0..2 {
+            assert!(matches!(
+                accessor(&glb, index).read_vec3_f32(&glb),
+                Err(Error::TooLarge {
+                    max: MAX_BUFFER_SIZE,
+                    ..
+                })
+            ));
+        }
+    }
+}
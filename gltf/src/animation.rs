@@ -0,0 +1,345 @@
+use glam::{Mat4, Quat, Vec3};
+
+use crate::{get, AnimationPath, Buffers, Error, Gltf, Interpolation, Result, Transform};
+
+/// The animatable state of every node in a glTF, indexed by node.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pose {
+    pub transforms: Vec<Transform>,
+    pub weights: Vec<Vec<f32>>,
+}
+
+impl Pose {
+    /// Every node in its rest position, with morph weights from the node
+    /// or else its mesh.
+    pub fn rest(gltf: &Gltf) -> Self {
+        Self {
+            transforms: gltf.nodes.iter().map(|node| node.transform()).collect(),
+            weights: gltf
+                .nodes
+                .iter()
+                .map(|node| {
+                    node.weights
+                        .as_ref()
+                        .or_else(|| {
+                            node.mesh
+                                .and_then(|mesh| gltf.meshes.get(mesh))
+                                .and_then(|mesh| mesh.weights.as_ref())
+                        })
+                        .map(|weights| weights.iter().map(|weight| *weight as f32).collect())
+                        .unwrap_or_default()
+                })
+                .collect(),
+        }
+    }
+
+    /// World transforms of every node in the default scene with the pose
+    /// applied, indexed by node.
+    pub fn world_matrices(&self, gltf: &Gltf) -> Result<Vec<Mat4>> {
+        gltf.world_matrices_with(|index, node| match self.transforms.get(index) {
+            Some(transform) => transform.matrix(),
+            None => node.local_matrix(),
+        })
+    }
+}
+
+struct Channel {
+    node: usize,
+    path: AnimationPath,
+    interpolation: Interpolation,
+    times: Vec<f32>,
+    // Flattened keyframe values, with in and out tangents around each value
+    // for cubic splines
+    values: Vec<f32>,
+    components: usize,
+}
+
+impl Channel {
+    fn value(&self, key: usize) -> &[f32] {
+        let key = match self.interpolation {
+            Interpolation::CubicSpline => key * 3 + 1,
+            _ => key,
+        };
+        &self.values[key * self.components..(key + 1) * self.components]
+    }
+
+    fn tangent(&self, key: usize, out: bool) -> &[f32] {
+        let key = key * 3 + if out { 2 } else { 0 };
+        &self.values[key * self.components..(key + 1) * self.components]
+    }
+
+    fn sample(&self, t: f32) -> Vec<f32> {
+        let last = self.times.len() - 1;
+        if t.is_nan() || t <= self.times[0] {
+            return self.value(0).to_vec();
+        }
+        if t >= self.times[last] {
+            return self.value(last).to_vec();
+        }
+
+        let next = self.times.partition_point(|time| *time <= t);
+        let key = next - 1;
+        let delta = self.times[next] - self.times[key];
+        let s = (t - self.times[key]) / delta;
+
+        match self.interpolation {
+            Interpolation::Step => self.value(key).to_vec(),
+            Interpolation::Linear if self.path == AnimationPath::Rotation => {
+                let a = Quat::from_slice(self.value(key));
+                let b = Quat::from_slice(self.value(next));
+                a.slerp(b, s).to_array().to_vec()
+            }
+            Interpolation::Linear => self
+                .value(key)
+                .iter()
+                .zip(self.value(next))
+                .map(|(a, b)| a + (b - a) * s)
+                .collect(),
+            Interpolation::CubicSpline => {
+                let (s2, s3) = (s * s, s * s * s);
+                let value = self.value(key);
+                let out = self.tangent(key, true);
+                let next_in = self.tangent(next, false);
+                let next_value = self.value(next);
+                let mut result: Vec<f32> = (0..self.components)
+                    .map(|i| {
+                        (2.0 * s3 - 3.0 * s2 + 1.0) * value[i]
+                            + (s3 - 2.0 * s2 + s) * delta * out[i]
+                            + (-2.0 * s3 + 3.0 * s2) * next_value[i]
+                            + (s3 - s2) * delta * next_in[i]
+                    })
+                    .collect();
+                if self.path == AnimationPath::Rotation {
+                    result = Quat::from_slice(&result).normalize().to_array().to_vec();
+                }
+                result
+            }
+        }
+    }
+}
+
+/// An animation with its keyframes read out of the buffers, ready to be
+/// sampled.
+pub struct Clip {
+    channels: Vec<Channel>,
+    duration: f32,
+}
+
+impl Clip {
+    pub fn load(glb: &impl Buffers, animation: usize) -> Result<Self> {
+        let animation = get(&glb.gltf().animations, "animation", animation)?;
+
+        let mut channels = Vec::new();
+        for channel in &animation.channels {
+            let (Some(node), path) = (channel.target.node, channel.target.path) else {
+                continue;
+            };
+            if path == AnimationPath::Unknown {
+                continue;
+            }
+            get(&glb.gltf().nodes, "node", node)?;
+
+            let sampler = get(&animation.samplers, "animation sampler", channel.sampler)?;
+            let times = get(&glb.gltf().accessors, "accessor", sampler.input)?.read_scalar(glb)?;
+            let values =
+                get(&glb.gltf().accessors, "accessor", sampler.output)?.read_floats(glb)?;
+            if times.is_empty() {
+                continue;
+            }
+            // Sampling relies on there being a gap between every keyframe
+            if times.iter().any(|time| !time.is_finite())
+                || times.windows(2).any(|pair| pair[0] >= pair[1])
+            {
+                return Err(Error::KeyframeTimes(sampler.input));
+            }
+
+            let keys = match sampler.interpolation {
+                Interpolation::CubicSpline => times.len() * 3,
+                _ => times.len(),
+            };
+            let components = match path {
+                AnimationPath::Translation | AnimationPath::Scale => 3,
+                AnimationPath::Rotation => 4,
+                _ => values.len() / keys,
+            };
+            if values.len() != keys * components {
+                return Err(Error::Keyframes {
+                    expected: keys * components,
+                    found: values.len(),
+                });
+            }
+
+            channels.push(Channel {
+                node,
+                path,
+                interpolation: sampler.interpolation,
+                times,
+                values,
+                components,
+            });
+        }
+
+        let duration = channels
+            .iter()
+            .filter_map(|channel| channel.times.last())
+            .fold(0.0, |duration: f32, time| duration.max(*time));
+
+        Ok(Self { channels, duration })
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.duration
+    }
+
+    /// Overwrites everything the clip animates in `pose` with its value at
+    /// `t` seconds, holding the first and last keyframes outside the clip.
+    pub fn sample(&self, t: f32, pose: &mut Pose) {
+        for channel in &self.channels {
+            let value = channel.sample(t);
+            if let AnimationPath::Weights = channel.path {
+                if let Some(weights) = pose.weights.get_mut(channel.node) {
+                    *weights = value;
+                }
+                continue;
+            }
+            let Some(transform) = pose.transforms.get_mut(channel.node) else {
+                continue;
+            };
+            match channel.path {
+                AnimationPath::Translation => transform.translation = Vec3::from_slice(&value),
+                AnimationPath::Rotation => transform.rotation = Quat::from_slice(&value),
+                AnimationPath::Scale => transform.scale = Vec3::from_slice(&value),
+                AnimationPath::Weights | AnimationPath::Unknown => (),
+            }
+        }
+    }
+
+    /// The rest pose with the clip applied at `t` seconds.
+    pub fn pose(&self, gltf: &Gltf, t: f32) -> Pose {
+        let mut pose = Pose::rest(gltf);
+        self.sample(t, &mut pose);
+        pose
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{fixture, Glb, GlbRef};
+
+    fn glb(times: &[f32], translations: &[Vec3], interpolation: &str) -> Glb {
+        fixture::glb(|glb| {
+            json!({
+                "nodes": [{}],
+                "animations": [{
+                    "channels": [{"sampler": 0, "target": {"node": 0, "path": "translation"}}],
+                    "samplers": [{
+                        "input": glb.push_accessor(times, None),
+                        "output": glb.push_accessor(translations, None),
+                        "interpolation": interpolation,
+                    }],
+                }],
+            })
+        })
+    }
+
+    fn translation(clip: &Clip, glb: &Glb, t: f32) -> Vec3 {
+        clip.pose(&glb.gltf, t).transforms[0].translation
+    }
+
+    #[test]
+    fn sampling() {
+        let glb = glb(&[1.0, 2.0, 4.0], &[Vec3::ZERO, Vec3::X, Vec3::Y], "LINEAR");
+        let clip = Clip::load(&glb, 0).unwrap();
+        assert_eq!(clip.duration(), 4.0);
+        assert_eq!(translation(&clip, &glb, 0.0), Vec3::ZERO);
+        assert_eq!(translation(&clip, &glb, 1.5), Vec3::X * 0.5);
+        assert_eq!(translation(&clip, &glb, 2.0), Vec3::X);
+        assert_eq!(translation(&clip, &glb, 3.0), Vec3::new(0.5, 0.5, 0.0));
+        assert_eq!(translation(&clip, &glb, 10.0), Vec3::Y);
+
+        let glb = self::glb(&[1.0, 2.0], &[Vec3::ZERO, Vec3::X], "STEP");
+        let clip = Clip::load(&glb, 0).unwrap();
+        assert_eq!(translation(&clip, &glb, 1.9), Vec3::ZERO);
+        assert_eq!(translation(&clip, &glb, 2.0), Vec3::X);
+    }
+
+    #[test]
+    fn borrowed_clips() {
+        let bytes = glb(&[1.0, 2.0], &[Vec3::ZERO, Vec3::X], "LINEAR")
+            .to_bytes()
+            .unwrap();
+        let glb = GlbRef::load(&bytes).unwrap();
+        let clip = Clip::load(&glb, 0).unwrap();
+        let pose = clip.pose(&glb.gltf, 1.5);
+        assert_eq!(pose.transforms[0].translation, Vec3::X * 0.5);
+        assert!(crate::validate(&glb).is_empty());
+    }
+
+    #[test]
+    fn nan_samples_the_first_key() {
+        let glb = glb(&[1.0, 2.0], &[Vec3::Z, Vec3::X], "LINEAR");
+        let clip = Clip::load(&glb, 0).unwrap();
+        assert_eq!(translation(&clip, &glb, f32::NAN), Vec3::Z);
+    }
+
+    #[test]
+    fn weights() {
+        let glb = fixture::glb(|glb| {
+            json!({
+                "nodes": [{"weights": [0.0, 0.0]}],
+                "animations": [{
+                    "channels": [{"sampler": 0, "target": {"node": 0, "path": "weights"}}],
+                    "samplers": [{
+                        "input": glb.push_accessor(&[0.0f32, 1.0], None),
+                        "output": glb.push_accessor(&[0.0f32, 1.0, 1.0, 0.0], None),
+                    }],
+                }],
+            })
+        });
+        let clip = Clip::load(&glb, 0).unwrap();
+        assert_eq!(clip.pose(&glb.gltf, 0.5).weights, [[0.5, 0.5]]);
+
+        // A pose without room for the node's weights is left alone
+        let mut pose = Pose {
+            transforms: vec![Transform::default()],
+            weights: Vec::new(),
+        };
+        clip.sample(0.5, &mut pose);
+        assert!(pose.weights.is_empty());
+    }
+
+    #[test]
+    fn times_must_be_finite_and_increasing() {
+        for times in [
+            [0.0, 1.0, 1.0],
+            [0.0, 2.0, 1.0],
+            [0.0, f32::NAN, 1.0],
+            [0.0, 1.0, f32::INFINITY],
+            [f32::NEG_INFINITY, 0.0, 1.0],
+        ] {
+            let glb = glb(&times, &[Vec3::ZERO; 3], "LINEAR");
+            assert!(
+                matches!(Clip::load(&glb, 0), Err(Error::KeyframeTimes(0))),
+                "{times:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn keyframe_counts_must_match() {
+        let glb = glb(&[0.0, 1.0], &[Vec3::ZERO; 3], "LINEAR");
+        assert!(matches!(
+            Clip::load(&glb, 0),
+            Err(Error::Keyframes {
+                expected: 6,
+                found: 9
+            })
+        ));
+        // Cubic splines have tangents either side of each value
+        let glb = self::glb(&[0.0, 1.0], &[Vec3::ZERO; 6], "CUBICSPLINE");
+        assert!(Clip::load(&glb, 0).is_ok());
+    }
+}
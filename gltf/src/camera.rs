@@ -0,0 +1,113 @@
+use glam::Mat4;
+
+use crate::{Camera, CameraType, Error, Result};
+
+impl Camera {
+    /// A right handed projection with a depth range of 0 to 1. Perspective
+    /// cameras use their own aspect ratio if they have one, otherwise
+    /// `aspect`, and an infinite far plane if they don't have a `zfar`.
+    pub fn projection(&self, aspect: f32) -> Result<Mat4> {
+        match self.ty {
+            CameraType::Perspective => {
+                let perspective = self.perspective.ok_or(Error::Camera(self.ty))?;
+                let aspect = perspective.aspect_ratio.unwrap_or(aspect);
+                Ok(match perspective.zfar {
+                    Some(zfar) => {
+                        Mat4::perspective_rh(perspective.yfov, aspect, perspective.znear, zfar)
+                    }
+                    None => {
+                        Mat4::perspective_infinite_rh(perspective.yfov, aspect, perspective.znear)
+                    }
+                })
+            }
+            CameraType::Orthographic => {
+                let orthographic = self.orthographic.ok_or(Error::Camera(self.ty))?;
+                Ok(Mat4::orthographic_rh(
+                    -orthographic.xmag,
+                    orthographic.xmag,
+                    -orthographic.ymag,
+                    orthographic.ymag,
+                    orthographic.znear,
+                    orthographic.zfar,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use serde_json::{json, Value};
+
+    use super::*;
+
+    fn camera(json: Value) -> Camera {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn perspective() {
+        let camera = self::camera(json!({
+            "type": "perspective",
+            "perspective": {"yfov": 1.0, "znear": 0.5, "zfar": 100.0, "aspectRatio": 2.0},
+        }));
+        // The camera's own aspect ratio wins
+        let projection = camera.projection(1.0).unwrap();
+        assert_eq!(projection, Mat4::perspective_rh(1.0, 2.0, 0.5, 100.0));
+        assert!(projection
+            .project_point3(Vec3::new(0.0, 0.0, -0.5))
+            .abs_diff_eq(Vec3::ZERO, 1e-6));
+        assert!(projection
+            .project_point3(Vec3::new(0.0, 0.0, -100.0))
+            .abs_diff_eq(Vec3::Z, 1e-6));
+    }
+
+    #[test]
+    fn infinite_perspective() {
+        let camera = self::camera(json!({
+            "type": "perspective",
+            "perspective": {"yfov": 1.0, "znear": 0.5},
+        }));
+        let projection = camera.projection(1.5).unwrap();
+        assert_eq!(projection, Mat4::perspective_infinite_rh(1.0, 1.5, 0.5));
+        let far = projection.project_point3(Vec3::new(0.0, 0.0, -1e6));
+        assert!(far.z > 0.99 && far.z <= 1.0);
+    }
+
+    #[test]
+    fn orthographic() {
+        let camera = self::camera(json!({
+            "type": "orthographic",
+            "orthographic": {"xmag": 2.0, "ymag": 3.0, "znear": 1.0, "zfar": 11.0},
+        }));
+        // The aspect ratio only applies to perspective cameras
+        let projection = camera.projection(10.0).unwrap();
+        assert!(projection
+            .project_point3(Vec3::new(2.0, 3.0, -1.0))
+            .abs_diff_eq(Vec3::new(1.0, 1.0, 0.0), 1e-6));
+        assert!(projection
+            .project_point3(Vec3::new(-2.0, -3.0, -11.0))
+            .abs_diff_eq(Vec3::new(-1.0, -1.0, 1.0), 1e-6));
+    }
+
+    #[test]
+    fn missing_projections() {
+        for ty in ["perspective", "orthographic"] {
+            let camera = self::camera(json!({"type": ty}));
+            assert!(matches!(
+                camera.projection(1.0),
+                Err(Error::Camera(error)) if error == camera.ty
+            ));
+        }
+        // The other type's block doesn't count
+        let camera = self::camera(json!({
+            "type": "orthographic",
+            "perspective": {"yfov": 1.0, "znear": 0.5},
+        }));
+        assert!(matches!(
+            camera.projection(1.0),
+            Err(Error::Camera(CameraType::Orthographic))
+        ));
+    }
+}
@@ -0,0 +1,133 @@
+use std::fmt::Display;
+
+use crate::{CameraType, ComponentType, Mode};
+
+#[derive(Debug)]
+pub enum Error {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    Magic(u32),
+    Version(u32),
+    Truncated {
+        needed: usize,
+        available: usize,
+    },
+    ChunkType {
+        expected: u32,
+        found: u32,
+    },
+    Index {
+        kind: &'static str,
+        index: usize,
+    },
+    Bounds {
+        start: usize,
+        end: usize,
+        length: usize,
+    },
+    ElementType(String),
+    UnexpectedType {
+        expected: &'static [&'static str],
+        found: String,
+    },
+    ComponentType(ComponentType),
+    Uri(String),
+    Keyframes {
+        expected: usize,
+        found: usize,
+    },
+    KeyframeTimes(usize),
+    Count {
+        expected: usize,
+        found: usize,
+    },
+    Camera(CameraType),
+    Mode(Mode),
+    ImageFormat,
+    Png(String),
+    Jpeg(String),
+    Meshopt(&'static str),
+    Extension(String),
+    Cycle(usize),
+    SharedNode(usize),
+    Tangents,
+    TooLarge {
+        size: usize,
+        max: usize,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read glTF: {e}"),
+            Self::Json(e) => write!(f, "Failed to parse glTF: {e}"),
+            Self::Magic(magic) => write!(f, "Not a GLB file, magic was {magic:#010x}"),
+            Self::Version(version) => {
+                write!(f, "Only glTF 2.0 is supported, file is version {version}")
+            }
+            Self::Truncated { needed, available } => write!(
+                f,
+                "GLB is truncated, needed {needed} bytes but only {available} are left"
+            ),
+            Self::ChunkType { expected, found } => write!(
+                f,
+                "Expected a chunk of type {expected:#010x} but found {found:#010x}"
+            ),
+            Self::Index { kind, index } => write!(f, "There is no {kind} {index}"),
+            Self::Bounds { start, end, length } => write!(
+                f,
+                "Bytes {start}..{end} are outside of a buffer of length {length}"
+            ),
+            Self::ElementType(ty) => write!(f, "Invalid accessor type {ty}"),
+            Self::UnexpectedType { expected, found } => write!(
+                f,
+                "Expected an accessor of type {} but found {found}",
+                expected.join(" or ")
+            ),
+            Self::ComponentType(ty) => write!(f, "Component type {ty:?} isn't allowed here"),
+            Self::Uri(uri) => write!(f, "Can't load URI {uri}"),
+            Self::Keyframes { expected, found } => {
+                write!(f, "Expected {expected} keyframe values but found {found}")
+            }
+            Self::KeyframeTimes(accessor) => write!(
+                f,
+                "Keyframe times in accessor {accessor} aren't finite and increasing"
+            ),
+            Self::Count { expected, found } => {
+                write!(f, "Expected {expected} elements but found {found}")
+            }
+            Self::Camera(ty) => write!(f, "Camera of type {ty:?} has no {ty:?} properties"),
+            Self::Mode(mode) => write!(f, "Primitives drawn as {mode:?} don't have triangles"),
+            Self::ImageFormat => write!(f, "Image isn't a PNG or a JPEG"),
+            Self::Png(reason) => write!(f, "Invalid PNG, {reason}"),
+            Self::Jpeg(reason) => write!(f, "Invalid JPEG, {reason}"),
+            Self::Meshopt(reason) => write!(f, "Invalid meshopt compressed data, {reason}"),
+            Self::Extension(extension) => {
+                write!(f, "Required extension {extension} isn't supported")
+            }
+            Self::Cycle(node) => write!(f, "Node {node} is its own ancestor"),
+            Self::SharedNode(node) => write!(f, "Node {node} has more than one parent"),
+            Self::Tangents => write!(f, "Couldn't generate tangents for the mesh"),
+            Self::TooLarge { size, max } => {
+                write!(f, "Allocating {size} bytes is over the limit of {max}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
@@ -0,0 +1,35 @@
+//! Small GLBs for tests, built from typed arrays and a bit of JSON.
+
+use serde_json::{json, Value};
+
+use crate::{Glb, Gltf};
+
+/// Builds a GLB from the JSON `json` returns, keeping the buffers, views and
+/// accessors it pushes along the way.
+pub fn glb(json: impl FnOnce(&mut Glb) -> Value) -> Glb {
+    let mut glb = Glb::new();
+    let mut json = json(&mut glb);
+    json["asset"] = json!({"version": "2.0"});
+    let mut gltf: Gltf = serde_json::from_value(json).unwrap();
+    gltf.buffers = std::mem::take(&mut glb.gltf.buffers);
+    gltf.buffer_views = std::mem::take(&mut glb.gltf.buffer_views);
+    gltf.accessors = std::mem::take(&mut glb.gltf.accessors);
+    glb.gltf = gltf;
+    glb
+}
+
+/// Builds a GLB from hand written buffer views and accessors over `bin`, for
+/// layouts `push_accessor` doesn't make, like strides and sparse accessors.
+pub fn raw(buffer_views: Value, accessors: Value, bin: Vec<u8>) -> Glb {
+    let gltf = json!({
+        "asset": {"version": "2.0"},
+        "buffers": [{"byteLength": bin.len()}],
+        "bufferViews": buffer_views,
+        "accessors": accessors,
+    });
+    Glb {
+        gltf: serde_json::from_value(gltf).unwrap(),
+        buffers: vec![bin],
+        images: Vec::new(),
+    }
+}
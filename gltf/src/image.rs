@@ -0,0 +1,90 @@
+use crate::{jpeg, png, Error, Result};
+
+/// The most memory decoding an image can take, enough for an 8192 by 8192
+/// RGBA texture.
+pub(crate) const MAX_SIZE: usize = 8192 * 8192 * 4;
+
+/// A decoded image as RGBA8, row by row from the top left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pixels {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Pixels {
+    /// Decodes a PNG or a JPEG, going by its contents rather than any MIME
+    /// type.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        if bytes.starts_with(&png::SIGNATURE) {
+            png::decode(bytes)
+        } else if bytes.starts_with(&jpeg::SOI) {
+            jpeg::decode(bytes)
+        } else {
+            Err(Error::ImageFormat)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{fixture, Buffers, Glb, GlbRef};
+
+    const PNG: &[u8] = include_bytes!("../testdata/images/palette_trns.png");
+    const JPEG: &[u8] = include_bytes!("../testdata/images/grey.jpg");
+
+    // A texture for an image in a buffer view, and one for a data URI
+    fn textures() -> Vec<u8> {
+        fixture::glb(|glb| {
+            let png = glb.push_accessor(PNG, None);
+            json!({
+                "images": [
+                    {"bufferView": glb.gltf.accessors[png].buffer_view, "mimeType": "image/png"},
+                    {"uri": format!("data:image/jpeg;base64,{}", STANDARD.encode(JPEG))},
+                ],
+                "textures": [{"source": 1}, {"source": 0}],
+            })
+        })
+        .to_bytes()
+        .unwrap()
+    }
+
+    fn decode_textures(glb: &impl Buffers) {
+        assert_eq!(glb.image_data(0).unwrap(), PNG);
+        assert_eq!(glb.image_data(1).unwrap(), JPEG);
+        assert_eq!(glb.decode_texture(1).unwrap(), Pixels::decode(PNG).unwrap());
+        assert_eq!(
+            glb.decode_texture(0).unwrap(),
+            Pixels::decode(JPEG).unwrap()
+        );
+        assert!(glb.decode_texture(2).is_err());
+        assert!(glb.image_data(2).is_err());
+    }
+
+    #[test]
+    fn owned_textures() {
+        decode_textures(&Glb::load(&textures()).unwrap());
+    }
+
+    #[test]
+    fn borrowed_textures() {
+        let bytes = textures();
+        decode_textures(&GlbRef::load(&bytes).unwrap());
+    }
+
+    #[test]
+    fn images_need_data() {
+        let glb = fixture::glb(|_| json!({"images": [{"mimeType": "image/png"}]}));
+        assert!(matches!(
+            glb.image_data(0),
+            Err(Error::Index {
+                kind: "buffer view for image",
+                index: 0
+            })
+        ));
+    }
+}
@@ -0,0 +1,146 @@
+use jpeg_decoder::{Decoder, PixelFormat};
+
+use crate::{image::MAX_SIZE, Error, Pixels, Result};
+
+pub(crate) const SOI: [u8; 2] = [0xff, 0xd8];
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Pixels> {
+    let mut decoder = Decoder::new(bytes);
+    decoder.set_max_decoding_buffer_size(MAX_SIZE);
+    decoder
+        .read_info()
+        .map_err(|e| Error::Jpeg(e.to_string()))?;
+    let info = decoder
+        .info()
+        .ok_or(Error::Jpeg("missing frame header".to_owned()))?;
+    // The decoder only limits its own buffer, which is smaller than the
+    // RGBA one it gets expanded into
+    let size = info.width as usize * info.height as usize * 4;
+    if size > MAX_SIZE {
+        return Err(Error::Jpeg(format!(
+            "image is too big, it needs {size} bytes"
+        )));
+    }
+    let data = decoder.decode().map_err(|e| Error::Jpeg(e.to_string()))?;
+
+    let data = match info.pixel_format {
+        PixelFormat::RGB24 => data
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+            .collect(),
+        PixelFormat::L8 => data
+            .iter()
+            .flat_map(|grey| [*grey, *grey, *grey, 255])
+            .collect(),
+        // Native endian, and only as many bits as the frame's precision, so
+        // 12 bit samples don't fill the whole u16
+        PixelFormat::L16 => {
+            let shift = precision(bytes)
+                .ok_or(Error::Jpeg("missing frame header".to_owned()))?
+                .saturating_sub(8);
+            data.chunks_exact(2)
+                .flat_map(|grey| {
+                    let grey = (u16::from_ne_bytes([grey[0], grey[1]]) >> shift) as u8;
+                    [grey, grey, grey, 255]
+                })
+                .collect()
+        }
+        PixelFormat::CMYK32 => {
+            return Err(Error::Jpeg(
+                "only greyscale and YCbCr images are supported".to_owned(),
+            ))
+        }
+    };
+
+    Ok(Pixels {
+        width: info.width as u32,
+        height: info.height as u32,
+        data,
+    })
+}
+
+/// Bits per sample, from the first start of frame marker.
+fn precision(bytes: &[u8]) -> Option<u8> {
+    let mut i = SOI.len();
+    loop {
+        let [0xff, marker, high, low, ..] = *bytes.get(i..)? else {
+            return None;
+        };
+        // SOF0 to SOF15, apart from DHT, JPG and DAC
+        if matches!(marker, 0xc0..=0xcf) && !matches!(marker, 0xc4 | 0xc8 | 0xcc) {
+            return bytes.get(i + 4).copied();
+        }
+        i += 2 + u16::from_be_bytes([high, low]) as usize;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Lossy, so pixels are only checked to be close to the gradients
+    // testdata/images was generated with
+    const TOLERANCE: u8 = 8;
+
+    fn check(pixels: &Pixels, width: u32, height: u32, expected: impl Fn(u32, u32) -> [u8; 3]) {
+        assert_eq!((pixels.width, pixels.height), (width, height));
+        let mut error = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let i = ((y * width + x) * 4) as usize;
+                for (actual, expected) in pixels.data[i..i + 3].iter().zip(expected(x, y)) {
+                    error = error.max(actual.abs_diff(expected));
+                }
+                assert_eq!(pixels.data[i + 3], 255);
+            }
+        }
+        assert!(error <= TOLERANCE, "{error}");
+    }
+
+    #[test]
+    fn rgb() {
+        let pixels = decode(include_bytes!("../testdata/images/444.jpg")).unwrap();
+        check(&pixels, 16, 16, |x, y| [(x * 6) as u8, (y * 6) as u8, 128]);
+    }
+
+    #[test]
+    fn greyscale() {
+        let pixels = decode(include_bytes!("../testdata/images/grey.jpg")).unwrap();
+        check(&pixels, 24, 16, |x, y| [(x * 6 + y * 3) as u8; 3]);
+    }
+
+    #[test]
+    fn high_precision() {
+        // Lossless, with noise in the bits below the top 8
+        for image in [
+            &include_bytes!("../testdata/images/grey12.jpg")[..],
+            &include_bytes!("../testdata/images/grey16.jpg")[..],
+        ] {
+            let pixels = decode(image).unwrap();
+            check(&pixels, 24, 16, |x, y| [(x * 6 + y * 3) as u8; 3]);
+        }
+    }
+
+    #[test]
+    fn invalid() {
+        let image = include_bytes!("../testdata/images/444.jpg");
+        assert!(matches!(decode(&image[..200]), Err(Error::Jpeg(_))));
+        assert!(matches!(decode(&SOI), Err(Error::Jpeg(_))));
+    }
+
+    #[test]
+    fn too_big() {
+        // 8193 by 8192, which fits the decoder's greyscale buffer but not
+        // the RGBA one
+        let mut image = include_bytes!("../testdata/images/grey.jpg").to_vec();
+        let sof = image
+            .windows(2)
+            .position(|marker| marker == [0xff, 0xc0])
+            .unwrap();
+        image[sof + 5..sof + 9].copy_from_slice(&[0x20, 0x00, 0x20, 0x01]);
+        assert!(matches!(
+            decode(&image),
+            Err(Error::Jpeg(e)) if e.contains("too big")
+        ));
+    }
+}
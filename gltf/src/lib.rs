@@ -1,11 +1,70 @@
-#![feature(exact_size_is_empty)]
+mod accessor;
+mod animation;
+mod camera;
+mod error;
+#[cfg(test)]
+mod fixture;
+mod image;
+mod jpeg;
+mod meshopt;
+mod morph;
+mod names;
+mod normals;
+mod optimize;
+mod png;
+mod scene;
+mod skin;
+mod topology;
+mod uri;
+mod validate;
+mod view;
+mod write;
 
-use std::{collections::HashMap, fmt::Debug};
+pub use animation::{Clip, Pose};
+pub use error::{Error, Result};
+pub use image::Pixels;
+pub use morph::{Morph, MorphTarget, Vertices};
+pub use normals::{flat_normals, smooth_normals, tangents, unweld, Tangents};
+pub use optimize::{
+    acmr, deduplicate, optimize, optimize_overdraw, optimize_vertex_cache, optimize_vertex_fetch,
+    Optimized, CACHE_SIZE,
+};
+pub use scene::{SceneNode, Transform};
+pub use skin::Skeleton;
+pub use validate::{validate, Issue};
+pub use view::View;
+pub use write::Element;
+
+use std::{borrow::Cow, collections::HashMap, fmt::Debug, path::Path};
 
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
-#[derive(Deserialize_repr, Serialize_repr, Debug)]
+fn get<'a, T>(items: &'a [T], kind: &'static str, index: usize) -> Result<&'a T> {
+    items.get(index).ok_or(Error::Index { kind, index })
+}
+
+/// The largest buffer that's allocated up front rather than read from a
+/// file, since its length comes straight from the JSON.
+pub(crate) const MAX_BUFFER_SIZE: usize = 1 << 30;
+
+fn is_zero(value: &usize) -> bool {
+    *value == 0
+}
+
+fn slice(bytes: &[u8], start: usize, length: usize) -> Result<&[u8]> {
+    start
+        .checked_add(length)
+        .and_then(|end| bytes.get(start..end))
+        .ok_or(Error::Bounds {
+            start,
+            end: start.saturating_add(length),
+            length: bytes.len(),
+        })
+}
+
+#[derive(Deserialize_repr, Serialize_repr, Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u16)]
 pub enum ComponentType {
     I8 = 5120,
@@ -27,7 +86,7 @@ impl ComponentType {
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Accessor {
+pub struct SparseIndices {
     #[serde(rename = "bufferView")]
     pub buffer_view: usize,
     #[serde(default)]
@@ -35,60 +94,220 @@ pub struct Accessor {
     pub byte_offset: usize,
     #[serde(rename = "componentType")]
     pub component_type: ComponentType,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SparseValues {
+    #[serde(rename = "bufferView")]
+    pub buffer_view: usize,
+    #[serde(default)]
+    #[serde(rename = "byteOffset")]
+    pub byte_offset: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Sparse {
+    pub count: usize,
+    pub indices: SparseIndices,
+    pub values: SparseValues,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Accessor {
+    #[serde(default)]
+    #[serde(rename = "bufferView")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buffer_view: Option<usize>,
+    #[serde(default)]
+    #[serde(rename = "byteOffset")]
+    pub byte_offset: usize,
+    #[serde(rename = "componentType")]
+    pub component_type: ComponentType,
     #[serde(default)]
     pub normalized: bool,
     pub count: usize,
     #[serde(rename = "type")]
     pub element_type: String,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max: Option<Vec<f64>>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min: Option<Vec<f64>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sparse: Option<Sparse>,
 }
 
-impl Accessor {
-    pub fn get_data(&self, glb: &Glb) -> Vec<u8> {
-        let buffer_view = glb.gltf.buffer_views.get(self.buffer_view).unwrap();
-        let _buffer = glb.gltf.buffers.get(buffer_view.buffer).unwrap();
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AnimationPath {
+    Translation,
+    Rotation,
+    Scale,
+    Weights,
+    #[serde(other)]
+    Unknown,
+}
 
-        let offset = self.byte_offset + buffer_view.byte_offset;
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnimationTarget {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node: Option<usize>,
+    pub path: AnimationPath,
+}
 
-        let element_size = match self.element_type.as_str() {
-            "SCALAR" => 1,
-            "VEC2" => 2,
-            "VEC3" => 3,
-            "VEC4" | "MAT2" => 4,
-            "MAT3" => 9,
-            "MAT4" => 16,
-            _ => panic!("Invalid element type"),
-        };
-        let size = self.component_type.size_of() * element_size * self.count;
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnimationChannel {
+    pub sampler: usize,
+    pub target: AnimationTarget,
+}
 
-        glb.buffer[offset..(offset + size)].to_vec()
-    }
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Interpolation {
+    #[default]
+    Linear,
+    Step,
+    CubicSpline,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AnimationSampler {
+    pub input: usize,
+    #[serde(default)]
+    pub interpolation: Interpolation,
+    pub output: usize,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Animation {}
+pub struct Animation {
+    pub channels: Vec<AnimationChannel>,
+    pub samplers: Vec<AnimationSampler>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub extras: Value,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Asset {
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub copyright: Option<String>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub generator: Option<String>,
     pub version: String,
     #[serde(default)]
     #[serde(rename = "minVersion")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub min_version: Option<String>,
 }
 
+impl Default for Asset {
+    fn default() -> Self {
+        Self {
+            copyright: None,
+            generator: None,
+            version: String::from("2.0"),
+            min_version: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MeshoptBuffer {
+    /// The buffer has no useful data of its own, only the decompressed
+    /// buffer views that point into it.
+    #[serde(default)]
+    pub fallback: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct BufferExtensions {
+    #[serde(default)]
+    #[serde(rename = "EXT_meshopt_compression")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meshopt_compression: Option<MeshoptBuffer>,
+}
+
+impl BufferExtensions {
+    fn is_empty(&self) -> bool {
+        self.meshopt_compression.is_none()
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Buffer {
     #[serde(default)]
-    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub uri: Option<String>,
     #[serde(rename = "byteLength")]
     pub byte_length: usize,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BufferExtensions::is_empty")]
+    pub extensions: BufferExtensions,
+}
+
+impl Buffer {
+    fn is_fallback(&self) -> bool {
+        self.extensions
+            .meshopt_compression
+            .as_ref()
+            .is_some_and(|meshopt| meshopt.fallback)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MeshoptMode {
+    Attributes,
+    Triangles,
+    Indices,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum MeshoptFilter {
+    #[default]
+    None,
+    Octahedral,
+    Quaternion,
+    Exponential,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct MeshoptCompression {
+    pub buffer: usize,
+    #[serde(default)]
+    #[serde(rename = "byteOffset")]
+    pub byte_offset: usize,
+    #[serde(rename = "byteLength")]
+    pub byte_length: usize,
+    #[serde(rename = "byteStride")]
+    pub byte_stride: usize,
+    pub count: usize,
+    pub mode: MeshoptMode,
+    #[serde(default)]
+    pub filter: MeshoptFilter,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct BufferViewExtensions {
+    #[serde(default)]
+    #[serde(rename = "EXT_meshopt_compression")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub meshopt_compression: Option<MeshoptCompression>,
+}
+
+impl BufferViewExtensions {
+    fn is_empty(&self) -> bool {
+        self.meshopt_compression.is_none()
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -101,23 +320,161 @@ pub struct BufferView {
     pub byte_length: usize,
     #[serde(default)]
     #[serde(rename = "byteStride")]
+    #[serde(skip_serializing_if = "is_zero")]
     pub byte_stride: usize,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub target: Option<u32>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BufferViewExtensions::is_empty")]
+    pub extensions: BufferViewExtensions,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Perspective {
+    #[serde(default)]
+    #[serde(rename = "aspectRatio")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aspect_ratio: Option<f32>,
+    pub yfov: f32,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zfar: Option<f32>,
+    pub znear: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Orthographic {
+    pub xmag: f32,
+    pub ymag: f32,
+    pub zfar: f32,
+    pub znear: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CameraType {
+    Perspective,
+    Orthographic,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Camera {}
+pub struct Camera {
+    #[serde(rename = "type")]
+    pub ty: CameraType,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub perspective: Option<Perspective>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub orthographic: Option<Orthographic>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LightType {
+    Directional,
+    Point,
+    Spot,
+}
+
+fn default_light_color() -> [f32; 3] {
+    [1.0; 3]
+}
+
+fn default_light_intensity() -> f32 {
+    1.0
+}
+
+fn default_outer_cone_angle() -> f32 {
+    std::f32::consts::FRAC_PI_4
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct Spot {
+    #[serde(default)]
+    #[serde(rename = "innerConeAngle")]
+    pub inner_cone_angle: f32,
+    #[serde(default = "default_outer_cone_angle")]
+    #[serde(rename = "outerConeAngle")]
+    pub outer_cone_angle: f32,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Light {
+    #[serde(rename = "type")]
+    pub ty: LightType,
+    #[serde(default = "default_light_color")]
+    pub color: [f32; 3],
+    #[serde(default = "default_light_intensity")]
+    pub intensity: f32,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub range: Option<f32>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spot: Option<Spot>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Lights {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lights: Vec<Light>,
+}
+
+/// Extensions that are understood well enough to be required by an asset.
+pub const EXTENSIONS: &[&str] = &[
+    "EXT_meshopt_compression",
+    "KHR_lights_punctual",
+    "KHR_mesh_quantization",
+];
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct Extensions {
+    #[serde(default)]
+    #[serde(rename = "KHR_lights_punctual")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lights_punctual: Option<Lights>,
+}
+
+impl Extensions {
+    fn is_empty(&self) -> bool {
+        self.lights_punctual.is_none()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct NodeLight {
+    pub light: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct NodeExtensions {
+    #[serde(default)]
+    #[serde(rename = "KHR_lights_punctual")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lights_punctual: Option<NodeLight>,
+}
+
+impl NodeExtensions {
+    fn is_empty(&self) -> bool {
+        self.lights_punctual.is_none()
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Image {
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub uri: Option<String>,
     #[serde(default)]
     #[serde(rename = "mimeType")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mime_type: Option<String>,
     #[serde(default)]
     #[serde(rename = "bufferView")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub buffer_view: Option<usize>,
 }
 
@@ -133,18 +490,23 @@ pub struct TextureInfo {
 pub struct MaterialPBR {
     #[serde(default)]
     #[serde(rename = "baseColorFactor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub base_color_factor: Option<[f32; 4]>,
     #[serde(default)]
     #[serde(rename = "baseColorTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub base_color_texture: Option<TextureInfo>,
     #[serde(default)]
     #[serde(rename = "metallicFactor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub metallic_factor: Option<f32>,
     #[serde(default)]
     #[serde(rename = "roughnessFactor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub roughness_factor: Option<f32>,
     #[serde(default)]
     #[serde(rename = "metallicRoughnessTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub metallic_roughness_texture: Option<TextureInfo>,
 }
 
@@ -167,6 +529,7 @@ pub struct MaterialNormalTexture {
     #[serde(rename = "texCoord")]
     pub tex_coord: usize,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub scale: Option<f32>,
 }
 
@@ -177,6 +540,7 @@ pub struct MaterialOcclusionTexture {
     #[serde(rename = "texCoord")]
     pub tex_coord: usize,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub strength: Option<f32>,
 }
 
@@ -187,57 +551,94 @@ pub struct Material {
     pub pbr: MaterialPBR,
     #[serde(default)]
     #[serde(rename = "normalTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub normal_texture: Option<MaterialNormalTexture>,
     #[serde(default)]
     #[serde(rename = "occlusionTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub occlusion_texture: Option<MaterialOcclusionTexture>,
     #[serde(default)]
     #[serde(rename = "emissiveTexture")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub emissive_texture: Option<TextureInfo>,
     #[serde(default)]
-    #[serde(rename = "emissiveFavtor")]
+    #[serde(rename = "emissiveFactor")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub emissive_factor: Option<[f32; 3]>,
     #[serde(default)]
     #[serde(rename = "alphaMode")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alpha_mode: Option<String>,
     #[serde(default)]
     #[serde(rename = "alphaCutoff")]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub alpha_cutoff: Option<f32>,
     #[serde(default)]
     #[serde(rename = "doubleSided")]
     pub double_sided: bool,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub extras: Value,
+}
+
+#[derive(Deserialize_repr, Serialize_repr, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Mode {
+    Points = 0,
+    Lines = 1,
+    LineLoop = 2,
+    LineStrip = 3,
+    #[default]
+    Triangles = 4,
+    TriangleStrip = 5,
+    TriangleFan = 6,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MeshPrimitive {
     pub attributes: HashMap<String, usize>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub indices: Option<usize>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub material: Option<usize>,
+    #[serde(default)]
+    pub mode: Mode,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<HashMap<String, usize>>,
 }
 
 impl MeshPrimitive {
-    pub fn get_attribute_data(&self, glb: &Glb, attribute: &str) -> Option<Vec<u8>> {
-        glb.gltf
-            .accessors
-            .get(*self.attributes.get(attribute)?)
+    pub fn attribute<'a>(
+        &self,
+        glb: &'a impl Buffers,
+        attribute: &str,
+    ) -> Result<Option<&'a Accessor>> {
+        self.attributes
+            .get(attribute)
+            .map(|accessor| get(&glb.gltf().accessors, "accessor", *accessor))
+            .transpose()
+    }
+
+    pub fn get_attribute_data(
+        &self,
+        glb: &impl Buffers,
+        attribute: &str,
+    ) -> Result<Option<Vec<u8>>> {
+        self.attribute(glb, attribute)?
             .map(|accessor| accessor.get_data(glb))
+            .transpose()
     }
 
-    pub fn get_indices_data(&self, glb: &Glb) -> Option<Vec<u32>> {
-        glb.gltf.accessors.get(self.indices?).map(|accessor| {
-            let data = accessor.get_data(glb);
-            match accessor.component_type {
-                ComponentType::U16 => bytemuck::cast_slice::<u8, u16>(&data)
-                    .iter()
-                    .copied()
-                    .map(|short| short as u32)
-                    .collect(),
-                ComponentType::U32 => bytemuck::cast_slice::<u8, u32>(&data).to_vec(),
-                _ => panic!("Invalid index component type"),
-            }
-        })
+    pub fn get_indices_data(&self, glb: &impl Buffers) -> Result<Option<Vec<u32>>> {
+        self.indices
+            .map(|indices| get(&glb.gltf().accessors, "accessor", indices)?.read_indices(glb))
+            .transpose()
     }
 }
 
@@ -245,29 +646,54 @@ impl MeshPrimitive {
 pub struct Mesh {
     pub primitives: Vec<MeshPrimitive>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub weights: Option<Vec<f64>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub extras: Value,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Node {
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub camera: Option<usize>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub children: Vec<usize>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub skin: Option<usize>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub matrix: Option<[f32; 16]>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub mesh: Option<usize>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rotation: Option<[f32; 4]>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub scale: Option<[f32; 3]>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub translation: Option<[f32; 3]>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub weights: Option<Vec<f64>>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "NodeExtensions::is_empty")]
+    pub extensions: NodeExtensions,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub extras: Value,
 }
 
 #[derive(Deserialize_repr, Serialize_repr, Debug)]
@@ -320,115 +746,598 @@ pub struct Sampler {
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Scene {
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub nodes: Vec<usize>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
-pub struct Skin {}
+pub struct Skin {
+    #[serde(default)]
+    #[serde(rename = "inverseBindMatrices")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inverse_bind_matrices: Option<usize>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skeleton: Option<usize>,
+    pub joints: Vec<usize>,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Texture {
     #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub sampler: Option<usize>,
     pub source: usize,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Gltf {
     #[serde(default)]
     #[serde(rename = "extensionsUsed")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub extensions_used: Vec<String>,
     #[serde(default)]
     #[serde(rename = "extensionsRequired")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub extensions_required: Vec<String>,
 
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub accessors: Vec<Accessor>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub animations: Vec<Animation>,
     pub asset: Asset,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub buffers: Vec<Buffer>,
     #[serde(default)]
     #[serde(rename = "bufferViews")]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub buffer_views: Vec<BufferView>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub cameras: Vec<Camera>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub images: Vec<Image>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub materials: Vec<Material>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub meshes: Vec<Mesh>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub nodes: Vec<Node>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub samplers: Vec<Sampler>,
     #[serde(default)]
     pub scene: usize,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub scenes: Vec<Scene>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub skins: Vec<Skin>,
     #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
     pub textures: Vec<Texture>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Extensions::is_empty")]
+    pub extensions: Extensions,
 }
 
 impl Gltf {
-    fn load(bytes: &[u8]) -> serde_json::Result<Self> {
-        serde_json::from_slice(bytes)
+    pub fn load(bytes: &[u8]) -> Result<Self> {
+        let gltf: Self = serde_json::from_slice(bytes)?;
+        // Anything required but not understood would be loaded incorrectly
+        if let Some(extension) = gltf
+            .extensions_required
+            .iter()
+            .find(|extension| !EXTENSIONS.contains(&extension.as_str()))
+        {
+            return Err(Error::Extension(extension.clone()));
+        }
+        Ok(gltf)
     }
+
+    /// Lights from `KHR_lights_punctual`.
+    pub fn lights(&self) -> &[Light] {
+        self.extensions
+            .lights_punctual
+            .as_ref()
+            .map(|lights| lights.lights.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        if length > self.bytes.len() {
+            return Err(Error::Truncated {
+                needed: length,
+                available: self.bytes.len(),
+            });
+        }
+        let (taken, rest) = self.bytes.split_at(length);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let bytes = self.take(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    fn chunk(&mut self) -> Result<(u32, &'a [u8])> {
+        let length = self.u32()? as usize;
+        let ty = self.u32()?;
+        Ok((ty, self.take(length)?))
+    }
+}
+
+/// Where accessors read their data from, either a `Glb` that owns its
+/// buffers or a `GlbRef` that borrows them.
+pub trait Buffers {
+    fn gltf(&self) -> &Gltf;
+    fn buffer(&self, index: usize) -> Option<&[u8]>;
+    /// Contents of an image stored outside of the buffers, `None` for
+    /// images that point into a buffer view.
+    fn image(&self, index: usize) -> Option<&[u8]>;
+
+    /// A buffer view along with the bytes it covers.
+    fn buffer_view(&self, index: usize) -> Result<(&BufferView, &[u8])> {
+        let buffer_view = get(&self.gltf().buffer_views, "buffer view", index)?;
+        let buffer = self.buffer(buffer_view.buffer).ok_or(Error::Index {
+            kind: "buffer",
+            index: buffer_view.buffer,
+        })?;
+        Ok((
+            buffer_view,
+            slice(buffer, buffer_view.byte_offset, buffer_view.byte_length)?,
+        ))
+    }
+
+    /// The encoded contents of an image, from its buffer view, data URI or
+    /// file.
+    fn image_data(&self, index: usize) -> Result<&[u8]> {
+        let image = get(&self.gltf().images, "image", index)?;
+        if let Some(data) = self.image(index) {
+            return Ok(data);
+        }
+        let buffer_view = image.buffer_view.ok_or(Error::Index {
+            kind: "buffer view for image",
+            index,
+        })?;
+        Ok(self.buffer_view(buffer_view)?.1)
+    }
+
+    fn decode_image(&self, index: usize) -> Result<Pixels> {
+        Pixels::decode(self.image_data(index)?)
+    }
+
+    /// Decodes the image a texture samples, such as a material's base
+    /// colour texture.
+    fn decode_texture(&self, index: usize) -> Result<Pixels> {
+        self.decode_image(get(&self.gltf().textures, "texture", index)?.source)
+    }
+}
+
+// Loads every buffer, borrowing the binary chunk rather than copying it
+fn load_buffers<'a>(
+    gltf: &Gltf,
+    bin: Option<&'a [u8]>,
+    base: Option<&Path>,
+) -> Result<Vec<Cow<'a, [u8]>>> {
+    // Fallback buffers are allocated from their lengths alone, so between
+    // them they get as much as any one allocation
+    let fallback = gltf
+        .buffers
+        .iter()
+        .filter(|buffer| buffer.is_fallback())
+        .fold(0, |size: usize, buffer| {
+            size.saturating_add(buffer.byte_length)
+        });
+    if fallback > MAX_BUFFER_SIZE {
+        return Err(Error::TooLarge {
+            size: fallback,
+            max: MAX_BUFFER_SIZE,
+        });
+    }
+
+    let mut buffers = gltf
+        .buffers
+        .iter()
+        .enumerate()
+        .map(|(i, buffer)| {
+            let data = match (&buffer.uri, bin) {
+                // Filled in with the decompressed buffer views below
+                _ if buffer.is_fallback() => Cow::Owned(vec![0; buffer.byte_length]),
+                (Some(uri), _) => Cow::Owned(uri::load(uri, base)?),
+                // Only the first buffer can refer to the binary chunk
+                (None, Some(bin)) if i == 0 => Cow::Borrowed(bin),
+                (None, _) => {
+                    return Err(Error::Index {
+                        kind: "binary chunk for buffer",
+                        index: i,
+                    })
+                }
+            };
+            if data.len() < buffer.byte_length {
+                return Err(Error::Truncated {
+                    needed: buffer.byte_length,
+                    available: data.len(),
+                });
+            }
+            Ok(data)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    meshopt::decompress(gltf, &mut buffers)?;
+    Ok(buffers)
 }
 
+fn load_images(gltf: &Gltf, base: Option<&Path>) -> Result<Vec<Option<Vec<u8>>>> {
+    gltf.images
+        .iter()
+        .map(|image| {
+            image
+                .uri
+                .as_ref()
+                .map(|uri| uri::load(uri, base))
+                .transpose()
+        })
+        .collect()
+}
+
+/// A glTF asset with all of its buffers loaded into memory, whether it came
+/// from a `.glb` or a `.gltf` file.
 pub struct Glb {
     pub gltf: Gltf,
-    pub buffer: Vec<u8>,
+    pub buffers: Vec<Vec<u8>>,
+    /// Contents of images stored outside of the buffers, `None` for images
+    /// that point into a buffer view.
+    pub images: Vec<Option<Vec<u8>>>,
 }
 
 impl Glb {
-    fn get_u32(bytes: &mut impl Iterator<Item = u8>) -> u32 {
-        *bytemuck::from_bytes::<u32>(&bytes.take(4).collect::<Vec<u8>>())
+    const MAGIC: u32 = 0x46546C67;
+    const JSON: u32 = 0x4E4F534A;
+    const BIN: u32 = 0x004E4942;
+
+    /// Opens a `.glb` or `.gltf` file, resolving any external files it
+    /// references relative to its directory.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+        let base = path.parent().unwrap_or(Path::new(""));
+        if bytes.starts_with(&Self::MAGIC.to_le_bytes()) {
+            let (gltf, bin) = Self::parse(&bytes)?;
+            Self::resolve(gltf, bin, Some(base))
+        } else {
+            Self::load_gltf(&bytes, base)
+        }
+    }
+
+    /// Loads a binary `.glb`. Any buffers or images outside of it have to be
+    /// `data:` URIs, use `open` for files that reference other files.
+    pub fn load(bytes: &[u8]) -> Result<Self> {
+        let (gltf, bin) = Self::parse(bytes)?;
+        Self::resolve(gltf, bin, None)
     }
 
-    fn get(bytes: &mut impl Iterator<Item = u8>, length: usize) -> Vec<u8> {
-        bytes.take(length).collect()
+    /// Loads a JSON `.gltf`, reading relative URIs from `base`.
+    pub fn load_gltf<P: AsRef<Path>>(bytes: &[u8], base: P) -> Result<Self> {
+        Self::resolve(Gltf::load(bytes)?, None, Some(base.as_ref()))
     }
 
-    pub fn load(bytes: &[u8]) -> serde_json::Result<Self> {
-        let mut bytes = bytes.iter().copied();
+    fn parse(bytes: &[u8]) -> Result<(Gltf, Option<&[u8]>)> {
+        let mut reader = Reader { bytes };
 
-        let magic = Self::get_u32(&mut bytes);
-        if magic != 0x46546C67 {
-            panic!("Malformed GLB");
+        let magic = reader.u32()?;
+        if magic != Self::MAGIC {
+            return Err(Error::Magic(magic));
         }
 
-        let version = Self::get_u32(&mut bytes);
+        let version = reader.u32()?;
         if version != 2 {
-            panic!("Aetheria only supports glTF 2.0");
+            return Err(Error::Version(version));
         }
 
-        let _length = Self::get_u32(&mut bytes);
+        // Everything past the declared length isn't part of the GLB
+        let length = (reader.u32()? as usize).saturating_sub(12);
+        let mut reader = Reader {
+            bytes: reader.take(length)?,
+        };
 
-        let gltf_length = Self::get_u32(&mut bytes);
-        let gltf_type = Self::get_u32(&mut bytes);
-        if gltf_type != 0x4E4F534A {
-            panic!("Malformed GLB");
+        let (ty, json) = reader.chunk()?;
+        if ty != Self::JSON {
+            return Err(Error::ChunkType {
+                expected: Self::JSON,
+                found: ty,
+            });
         }
+        let gltf = Gltf::load(json)?;
 
-        let gltf_bytes: Vec<u8> = Self::get(&mut bytes, gltf_length as usize);
-        let gltf = Gltf::load(&gltf_bytes)?;
-
-        let mut buffer = Vec::new();
-        if !bytes.is_empty() {
-            let buffer_length = Self::get_u32(&mut bytes);
-            let buffer_type = Self::get_u32(&mut bytes);
-            if buffer_type != 0x004E4942 {
-                panic!("Malformed GLB");
+        // The binary chunk is optional, and any chunks after it are extensions we ignore
+        let mut bin = None;
+        if !reader.bytes.is_empty() {
+            let (ty, chunk) = reader.chunk()?;
+            if ty != Self::BIN {
+                return Err(Error::ChunkType {
+                    expected: Self::BIN,
+                    found: ty,
+                });
             }
+            bin = Some(chunk);
+        }
+
+        Ok((gltf, bin))
+    }
+
+    fn resolve(gltf: Gltf, bin: Option<&[u8]>, base: Option<&Path>) -> Result<Self> {
+        let buffers = load_buffers(&gltf, bin, base)?
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect();
+
+        let images = load_images(&gltf, base)?;
+        Ok(Self {
+            gltf,
+            buffers,
+            images,
+        })
+    }
+}
+
+impl Buffers for Glb {
+    fn gltf(&self) -> &Gltf {
+        &self.gltf
+    }
+
+    fn buffer(&self, index: usize) -> Option<&[u8]> {
+        self.buffers.get(index).map(Vec::as_slice)
+    }
+
+    fn image(&self, index: usize) -> Option<&[u8]> {
+        self.images.get(index)?.as_deref()
+    }
+}
+
+/// A `.glb` borrowed from memory, such as a memory mapped file. The binary
+/// chunk is read in place, only buffers and images in `data:` URIs are
+/// copied out.
+pub struct GlbRef<'a> {
+    pub gltf: Gltf,
+    pub buffers: Vec<Cow<'a, [u8]>>,
+    pub images: Vec<Option<Vec<u8>>>,
+}
+
+impl<'a> GlbRef<'a> {
+    pub fn load(bytes: &'a [u8]) -> Result<Self> {
+        let (gltf, bin) = Glb::parse(bytes)?;
+        let buffers = load_buffers(&gltf, bin, None)?;
+        let images = load_images(&gltf, None)?;
+        Ok(Self {
+            gltf,
+            buffers,
+            images,
+        })
+    }
+}
+
+impl Buffers for GlbRef<'_> {
+    fn gltf(&self) -> &Gltf {
+        &self.gltf
+    }
+
+    fn buffer(&self, index: usize) -> Option<&[u8]> {
+        self.buffers.get(index).map(|buffer| &**buffer)
+    }
+
+    fn image(&self, index: usize) -> Option<&[u8]> {
+        self.images.get(index)?.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    // A GLB with a JSON chunk and a binary chunk
+    fn glb() -> Vec<u8> {
+        let mut glb = Glb::new();
+        glb.push_accessor(&[1.0f32, 2.0], None);
+        glb.to_bytes().unwrap()
+    }
+
+    fn set_u32(bytes: &mut [u8], offset: usize, value: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn json_length(bytes: &[u8]) -> usize {
+        u32::from_le_bytes([bytes[12], bytes[13], bytes[14], bytes[15]]) as usize
+    }
+
+    #[test]
+    fn valid() {
+        let bytes = glb();
+        let glb = Glb::load(&bytes).unwrap();
+        assert_eq!(glb.buffers[0].len(), 8);
+        assert!(GlbRef::load(&bytes).is_ok());
+    }
+
+    #[test]
+    fn magic() {
+        let mut bytes = glb();
+        bytes[..4].copy_from_slice(b"{\"as");
+        assert!(matches!(Glb::load(&bytes), Err(Error::Magic(0x7361227b))));
+    }
+
+    #[test]
+    fn version() {
+        let mut bytes = glb();
+        set_u32(&mut bytes, 4, 1);
+        assert!(matches!(Glb::load(&bytes), Err(Error::Version(1))));
+    }
+
+    #[test]
+    fn truncated_headers() {
+        let bytes = glb();
+        assert!(matches!(
+            Glb::load(&[]),
+            Err(Error::Truncated {
+                needed: 4,
+                available: 0
+            })
+        ));
+        assert!(matches!(
+            Glb::load(&bytes[..10]),
+            Err(Error::Truncated {
+                needed: 4,
+                available: 2
+            })
+        ));
+        // The JSON chunk's header is cut short
+        let mut short = bytes[..16].to_vec();
+        set_u32(&mut short, 8, 16);
+        assert!(matches!(
+            Glb::load(&short),
+            Err(Error::Truncated {
+                needed: 4,
+                available: 0
+            })
+        ));
+    }
+
+    #[test]
+    fn declared_length() {
+        let bytes = glb();
+
+        // Longer than the file
+        let mut long = bytes.clone();
+        set_u32(&mut long, 8, bytes.len() as u32 + 4);
+        assert!(matches!(
+            Glb::load(&long),
+            Err(Error::Truncated { needed, available })
+                if needed == bytes.len() - 8 && available == bytes.len() - 12
+        ));
+
+        // Anything past the declared length is ignored
+        let mut trailing = bytes.clone();
+        trailing.extend_from_slice(b"junk");
+        assert!(Glb::load(&trailing).is_ok());
+
+        // Ending after the JSON chunk leaves the buffer without its data
+        let mut short = bytes.clone();
+        set_u32(&mut short, 8, 20 + json_length(&bytes) as u32);
+        assert!(matches!(
+            Glb::load(&short),
+            Err(Error::Index {
+                kind: "binary chunk for buffer",
+                index: 0
+            })
+        ));
+
+        // Ending partway through a chunk
+        let mut partial = bytes.clone();
+        set_u32(&mut partial, 8, bytes.len() as u32 - 4);
+        assert!(matches!(
+            Glb::load(&partial),
+            Err(Error::Truncated {
+                needed: 8,
+                available: 4
+            })
+        ));
+    }
+
+    #[test]
+    fn chunk_lengths() {
+        let bytes = glb();
+        let mut long = bytes.clone();
+        set_u32(&mut long, 12, bytes.len() as u32);
+        assert!(matches!(
+            Glb::load(&long),
+            Err(Error::Truncated { needed, available })
+                if needed == bytes.len() && available == bytes.len() - 20
+        ));
+    }
+
+    #[test]
+    fn chunk_types() {
+        let bytes = glb();
+        let mut json = bytes.clone();
+        set_u32(&mut json, 16, Glb::BIN);
+        assert!(matches!(
+            Glb::load(&json),
+            Err(Error::ChunkType {
+                expected: Glb::JSON,
+                found: Glb::BIN
+            })
+        ));
+
+        let mut bin = bytes.clone();
+        set_u32(&mut bin, 20 + json_length(&bytes) + 4, 0x12345678);
+        assert!(matches!(
+            Glb::load(&bin),
+            Err(Error::ChunkType {
+                expected: Glb::BIN,
+                found: 0x12345678
+            })
+        ));
+    }
+
+    // A .gltf with an external buffer and image, and a .glb that shares the
+    // image, with each saved under the other's extension too
+    #[test]
+    fn open_files() {
+        let dir = std::env::temp_dir().join(format!("gltf-open-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("textures")).unwrap();
+        let png = include_bytes!("../testdata/images/palette_trns.png");
+        std::fs::write(dir.join("textures/a.png"), png).unwrap();
+        std::fs::write(dir.join("data.bin"), bytemuck::cast_slice(&[1.0f32, 2.0])).unwrap();
+
+        let json = json!({
+            "asset": {"version": "2.0"},
+            "buffers": [{"uri": "data.bin", "byteLength": 8}],
+            "bufferViews": [{"buffer": 0, "byteLength": 8}],
+            "accessors": [{"bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR"}],
+            "images": [{"uri": "textures/a.png"}],
+        })
+        .to_string();
+        let mut binary = Glb::new();
+        binary.push_accessor(&[1.0f32, 2.0], None);
+        binary.gltf.images = serde_json::from_value(json!([{"uri": "textures/a.png"}])).unwrap();
+        let binary = binary.to_bytes().unwrap();
 
-            buffer = bytes.take(buffer_length as usize).collect();
+        for (name, bytes) in [
+            ("scene.gltf", json.as_bytes()),
+            ("scene.glb", &binary),
+            ("json.glb", json.as_bytes()),
+            ("binary.gltf", &binary),
+        ] {
+            let path = dir.join(name);
+            std::fs::write(&path, bytes).unwrap();
+            let glb = Glb::open(&path).unwrap();
+            assert_eq!(glb.gltf.accessors[0].read_scalar(&glb).unwrap(), [1.0, 2.0]);
+            assert_eq!(glb.image_data(0).unwrap(), png);
         }
 
-        Ok(Self { gltf, buffer })
+        let glb = Glb::load_gltf(json.as_bytes(), &dir).unwrap();
+        assert_eq!(glb.gltf.accessors[0].read_scalar(&glb).unwrap(), [1.0, 2.0]);
+        assert_eq!(glb.image_data(0).unwrap(), png);
+        // Relative to the wrong directory
+        assert!(matches!(
+            Glb::load_gltf(json.as_bytes(), dir.join("textures")),
+            Err(Error::Io(_))
+        ));
+        std::fs::remove_dir_all(&dir).unwrap();
     }
 }
@@ -0,0 +1,749 @@
+// A decoder for EXT_meshopt_compression buffer views, following the
+// bitstreams described in the extension's specification
+
+use std::borrow::Cow;
+
+use crate::{get, Error, Gltf, MeshoptCompression, MeshoptFilter, MeshoptMode, Result};
+
+fn unzigzag(value: u32) -> u32 {
+    (value >> 1) ^ (value & 1).wrapping_neg()
+}
+
+struct Input<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl Input<'_> {
+    fn take(&mut self, length: usize) -> Result<&[u8]> {
+        let bytes = self
+            .data
+            .get(self.position..self.position + length)
+            .ok_or(Error::Meshopt("data is truncated"))?;
+        self.position += length;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    // Little endian base 128, with at most five bytes
+    fn varint(&mut self) -> Result<u32> {
+        let mut value = 0;
+        for i in 0..5 {
+            let byte = self.byte()?;
+            value |= ((byte & 127) as u32) << (7 * i);
+            if byte < 128 {
+                break;
+            }
+        }
+        Ok(value)
+    }
+
+    fn finished(&self) -> Result<()> {
+        match self.position == self.data.len() {
+            true => Ok(()),
+            false => Err(Error::Meshopt("data has trailing bytes")),
+        }
+    }
+}
+
+// Vertex data is split into blocks of vertices, each block stored one byte
+// of the vertex at a time as deltas from the previous vertex
+const VERTEX_HEADER: u8 = 0xa0;
+const BLOCK_BYTES: usize = 8192;
+const BLOCK_MAX: usize = 256;
+const GROUP: usize = 16;
+const TAIL_MIN: usize = 32;
+
+// Decodes a group of 16 deltas of 0, 2, 4 or 8 bits. The largest 2 and 4
+// bit values mean the delta is in a full byte after the group instead
+fn group(input: &mut Input, mode: u8, out: &mut [u8]) -> Result<()> {
+    match mode {
+        0 => out.fill(0),
+        3 => out.copy_from_slice(input.take(GROUP)?),
+        _ => {
+            let bits = mode as usize * 2;
+            let packed = input.take(GROUP * bits / 8)?.to_vec();
+            let escape = (1 << bits) - 1;
+            for (i, out) in out.iter_mut().enumerate() {
+                let shift = 8 - bits - (i * bits) % 8;
+                let value = (packed[i * bits / 8] >> shift) & escape;
+                *out = if value == escape {
+                    input.byte()?
+                } else {
+                    value
+                };
+            }
+        }
+    }
+    Ok(())
+}
+
+fn decode_attributes(data: &[u8], count: usize, stride: usize) -> Result<Vec<u8>> {
+    if !stride.is_multiple_of(4) || !(4..=256).contains(&stride) {
+        return Err(Error::Meshopt(
+            "vertex stride isn't a multiple of 4 up to 256",
+        ));
+    }
+    if data.len() < 1 + stride.max(TAIL_MIN) {
+        return Err(Error::Meshopt("data is truncated"));
+    }
+    if data[0] != VERTEX_HEADER {
+        return Err(Error::Meshopt("unknown vertex encoding"));
+    }
+
+    // The first block's deltas are from the vertex stored at the very end
+    let mut last = data[data.len() - stride..].to_vec();
+    let mut input = Input {
+        data: &data[..data.len() - stride.max(TAIL_MIN)],
+        position: 1,
+    };
+
+    let block_size = ((BLOCK_BYTES / stride) & !(GROUP - 1)).min(BLOCK_MAX);
+    let mut out = vec![0; count * stride];
+    let mut deltas = [0; BLOCK_MAX];
+    for block in out.chunks_mut(block_size * stride) {
+        let vertices = block.len() / stride;
+        let groups = vertices.div_ceil(GROUP);
+        for k in 0..stride {
+            // Two bits per group saying how wide its deltas are
+            let header = input.take(groups.div_ceil(4))?.to_vec();
+            for (g, deltas) in deltas[..groups * GROUP].chunks_exact_mut(GROUP).enumerate() {
+                let mode = (header[g / 4] >> (g % 4 * 2)) & 3;
+                group(&mut input, mode, deltas)?;
+            }
+
+            let mut value = last[k];
+            for (vertex, delta) in deltas[..vertices].iter().enumerate() {
+                value = value.wrapping_add(unzigzag(*delta as u32) as u8);
+                block[vertex * stride + k] = value;
+            }
+            last[k] = value;
+        }
+    }
+
+    input.finished()?;
+    Ok(out)
+}
+
+// Triangles are stored as one code byte each, referring back to recently
+// seen edges and vertices, with any other indices in a separate stream
+const INDEX_HEADER: u8 = 0xe0;
+const SEQUENCE_HEADER: u8 = 0xd0;
+
+struct Fifos {
+    edges: [(u32, u32); 16],
+    edge: usize,
+    vertices: [u32; 16],
+    vertex: usize,
+}
+
+impl Fifos {
+    fn edge(&self, back: usize) -> (u32, u32) {
+        self.edges[(self.edge.wrapping_sub(1 + back)) & 15]
+    }
+
+    fn vertex(&self, back: usize) -> u32 {
+        self.vertices[(self.vertex.wrapping_sub(back)) & 15]
+    }
+
+    fn push_edge(&mut self, a: u32, b: u32) {
+        self.edges[self.edge] = (a, b);
+        self.edge = (self.edge + 1) & 15;
+    }
+
+    fn push_vertex(&mut self, v: u32, push: bool) {
+        self.vertices[self.vertex] = v;
+        self.vertex = (self.vertex + push as usize) & 15;
+    }
+}
+
+fn decode_triangles(data: &[u8], count: usize) -> Result<Vec<u32>> {
+    if !count.is_multiple_of(3) {
+        return Err(Error::Meshopt("index count isn't a multiple of 3"));
+    }
+    if data.len() < 1 + count / 3 + 16 {
+        return Err(Error::Meshopt("data is truncated"));
+    }
+    let version = data[0] & 15;
+    if data[0] & 0xf0 != INDEX_HEADER || version > 1 {
+        return Err(Error::Meshopt("unknown index encoding"));
+    }
+    // Version 1 uses the last two FIFO offsets for the last index plus or
+    // minus one instead
+    let fifo_max = if version == 0 { 15 } else { 13 };
+
+    // Codes 0xf0 to 0xfd pick vertices using a table at the end
+    let codes = &data[1..1 + count / 3];
+    let aux = &data[data.len() - 16..];
+    let mut input = Input {
+        data: &data[..data.len() - 16],
+        position: 1 + count / 3,
+    };
+
+    let mut fifos = Fifos {
+        edges: [(u32::MAX, u32::MAX); 16],
+        edge: 0,
+        vertices: [u32::MAX; 16],
+        vertex: 0,
+    };
+    let mut next = 0u32;
+    let mut last = 0u32;
+    let mut indices = Vec::with_capacity(count);
+    for &code in codes {
+        let (a, b, c) = if code < 0xf0 {
+            // An edge from the FIFO, and a vertex that's either new, from
+            // the FIFO, or stored relative to the last stored index
+            let (a, b) = fifos.edge((code >> 4) as usize);
+            let fec = (code & 15) as usize;
+            let c = match fec {
+                0 => {
+                    next = next.wrapping_add(1);
+                    next.wrapping_sub(1)
+                }
+                _ if fec < fifo_max => fifos.vertex(fec + 1),
+                13 | 14 => {
+                    last = last.wrapping_add(if fec == 13 { u32::MAX } else { 1 });
+                    last
+                }
+                _ => {
+                    last = last.wrapping_add(unzigzag(input.varint()?));
+                    last
+                }
+            };
+            fifos.push_vertex(c, fec == 0 || fec >= fifo_max);
+            fifos.push_edge(c, b);
+            fifos.push_edge(a, c);
+            (a, b, c)
+        } else {
+            // A triangle not sharing an edge with a recent one
+            let (fea, aux) = match code {
+                0xfe | 0xff => {
+                    let aux = input.byte()?;
+                    // A zero starts the new vertices from 0 again
+                    if aux == 0 {
+                        next = 0;
+                    }
+                    (code == 0xfe, aux)
+                }
+                _ => (true, aux[(code & 15) as usize]),
+            };
+            let (feb, fec) = ((aux >> 4) as usize, (aux & 15) as usize);
+
+            let mut vertex = |fe: usize, new: bool, input: &mut Input| -> Result<u32> {
+                Ok(match fe {
+                    _ if new => {
+                        next = next.wrapping_add(1);
+                        next.wrapping_sub(1)
+                    }
+                    15 => {
+                        last = last.wrapping_add(unzigzag(input.varint()?));
+                        last
+                    }
+                    _ => fifos.vertex(fe),
+                })
+            };
+            // Every vertex is taken from the FIFO before any are pushed
+            let a = vertex(15, fea, &mut input)?;
+            let b = vertex(feb, feb == 0, &mut input)?;
+            let c = vertex(fec, fec == 0, &mut input)?;
+
+            fifos.push_vertex(a, true);
+            fifos.push_vertex(b, feb == 0 || feb == 15);
+            fifos.push_vertex(c, fec == 0 || fec == 15);
+            fifos.push_edge(b, a);
+            fifos.push_edge(c, b);
+            fifos.push_edge(a, c);
+            (a, b, c)
+        };
+        indices.extend_from_slice(&[a, b, c]);
+    }
+
+    input.finished()?;
+    Ok(indices)
+}
+
+fn decode_indices(data: &[u8], count: usize) -> Result<Vec<u32>> {
+    if data.len() < 1 + count + 4 {
+        return Err(Error::Meshopt("data is truncated"));
+    }
+    if data[0] & 0xf0 != SEQUENCE_HEADER || data[0] & 15 > 1 {
+        return Err(Error::Meshopt("unknown index sequence encoding"));
+    }
+
+    let mut input = Input {
+        data: &data[..data.len() - 4],
+        position: 1,
+    };
+    // Deltas are from one of two previous indices, picked by the low bit
+    let mut last = [0u32; 2];
+    let mut indices = Vec::with_capacity(count);
+    for _ in 0..count {
+        let value = input.varint()?;
+        let baseline = (value & 1) as usize;
+        last[baseline] = last[baseline].wrapping_add(unzigzag(value >> 1));
+        indices.push(last[baseline]);
+    }
+
+    input.finished()?;
+    Ok(indices)
+}
+
+fn round(value: f32) -> i32 {
+    (value + if value >= 0.0 { 0.5 } else { -0.5 }) as i32
+}
+
+// Unit vectors stored as octahedral coordinates, in 8 or 16 bit components
+fn octahedral(data: &mut [u8], stride: usize) -> Result<()> {
+    let (size, max) = match stride {
+        4 => (1, 127.0),
+        8 => (2, 32767.0),
+        _ => return Err(Error::Meshopt("octahedral filter needs a stride of 4 or 8")),
+    };
+    let read = |bytes: &[u8]| match size {
+        1 => bytes[0] as i8 as f32,
+        _ => i16::from_le_bytes([bytes[0], bytes[1]]) as f32,
+    };
+    for element in data.chunks_exact_mut(stride) {
+        let mut x = read(&element[0..]);
+        let mut y = read(&element[size..]);
+        let z = read(&element[size * 2..]) - x.abs() - y.abs();
+
+        let t = z.min(0.0);
+        x += if x >= 0.0 { t } else { -t };
+        y += if y >= 0.0 { t } else { -t };
+        let scale = max / (x * x + y * y + z * z).sqrt();
+
+        for (i, value) in [x, y, z].into_iter().enumerate() {
+            let value = round(value * scale);
+            match size {
+                1 => element[i] = value as i8 as u8,
+                _ => element[i * 2..i * 2 + 2].copy_from_slice(&(value as i16).to_le_bytes()),
+            }
+        }
+    }
+    Ok(())
+}
+
+// Rotations as the three smallest quaternion components, with the index of
+// the largest one and a scale in the fourth
+fn quaternion(data: &mut [u8], stride: usize) -> Result<()> {
+    if stride != 8 {
+        return Err(Error::Meshopt("quaternion filter needs a stride of 8"));
+    }
+    for element in data.chunks_exact_mut(8) {
+        let read = |i: usize| i16::from_le_bytes([element[i * 2], element[i * 2 + 1]]);
+        let last = read(3);
+        let scale = std::f32::consts::FRAC_1_SQRT_2 / (last | 3) as f32;
+        let x = read(0) as f32 * scale;
+        let y = read(1) as f32 * scale;
+        let z = read(2) as f32 * scale;
+        let w = (1.0 - x * x - y * y - z * z).max(0.0).sqrt();
+
+        let largest = (last & 3) as usize;
+        let values = [
+            round(w * 32767.0),
+            round(x * 32767.0),
+            round(y * 32767.0),
+            round(z * 32767.0),
+        ];
+        for (i, value) in values.into_iter().enumerate() {
+            let index = (largest + i) & 3;
+            element[index * 2..index * 2 + 2].copy_from_slice(&(value as i16).to_le_bytes());
+        }
+    }
+    Ok(())
+}
+
+// Floats as a 24 bit mantissa and an 8 bit exponent
+fn exponential(data: &mut [u8], stride: usize) -> Result<()> {
+    if !stride.is_multiple_of(4) {
+        return Err(Error::Meshopt(
+            "exponential filter needs a multiple of 4 stride",
+        ));
+    }
+    for value in data.chunks_exact_mut(4) {
+        let bits = u32::from_le_bytes([value[0], value[1], value[2], value[3]]);
+        let mantissa = ((bits << 8) as i32) >> 8;
+        let exponent = (bits as i32) >> 24;
+        let power = f32::from_bits(((exponent + 127) as u32) << 23);
+        value.copy_from_slice(&(power * mantissa as f32).to_le_bytes());
+    }
+    Ok(())
+}
+
+fn decode(compression: &MeshoptCompression, data: &[u8]) -> Result<Vec<u8>> {
+    let (count, stride) = (compression.count, compression.byte_stride);
+    let indices = |indices: Vec<u32>| -> Result<Vec<u8>> {
+        Ok(match stride {
+            2 => indices
+                .into_iter()
+                .flat_map(|index| (index as u16).to_le_bytes())
+                .collect(),
+            4 => indices.into_iter().flat_map(u32::to_le_bytes).collect(),
+            _ => return Err(Error::Meshopt("index stride isn't 2 or 4")),
+        })
+    };
+
+    let mut out = match compression.mode {
+        MeshoptMode::Attributes => decode_attributes(data, count, stride)?,
+        MeshoptMode::Triangles => indices(decode_triangles(data, count)?)?,
+        MeshoptMode::Indices => indices(decode_indices(data, count)?)?,
+    };
+    match compression.filter {
+        MeshoptFilter::None => (),
+        _ if compression.mode != MeshoptMode::Attributes => {
+            return Err(Error::Meshopt("filters only apply to attributes"))
+        }
+        MeshoptFilter::Octahedral => octahedral(&mut out, stride)?,
+        MeshoptFilter::Quaternion => quaternion(&mut out, stride)?,
+        MeshoptFilter::Exponential => exponential(&mut out, stride)?,
+    }
+    Ok(out)
+}
+
+/// Decompresses every compressed buffer view into its place in its own
+/// buffer, which is usually an otherwise empty fallback buffer.
+pub(crate) fn decompress(gltf: &Gltf, buffers: &mut [Cow<[u8]>]) -> Result<()> {
+    for view in &gltf.buffer_views {
+        let Some(compression) = &view.extensions.meshopt_compression else {
+            continue;
+        };
+
+        // Checked before decoding so the output size is bounded by a buffer
+        // that has already been allocated
+        let length = compression
+            .count
+            .checked_mul(compression.byte_stride)
+            .filter(|length| *length <= view.byte_length)
+            .ok_or(Error::Meshopt(
+                "decompressed data is bigger than its buffer view",
+            ))?;
+        let end = view.byte_offset.saturating_add(length);
+        let buffer_length = get(buffers, "buffer", view.buffer)?.len();
+        if end > buffer_length {
+            return Err(Error::Bounds {
+                start: view.byte_offset,
+                end,
+                length: buffer_length,
+            });
+        }
+
+        let source = get(buffers, "buffer", compression.buffer)?;
+        let source = crate::slice(source, compression.byte_offset, compression.byte_length)?;
+        let data = decode(compression, source)?;
+        buffers[view.buffer].to_mut()[view.byte_offset..end].copy_from_slice(&data);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Buffers, Glb, MAX_BUFFER_SIZE};
+
+    // Test vectors from meshoptimizer's own tests, encoded by its reference
+    // encoder
+
+    // Four vertices of three u16 positions, two u8 normals and two u16 UVs,
+    // with the normals all zero so they're written here as one u16
+    const VERTICES: [[u16; 6]; 4] = [
+        [0, 0, 0, 0, 0, 0],
+        [300, 0, 0, 0, 500, 0],
+        [0, 300, 0, 0, 0, 500],
+        [300, 300, 0, 0, 500, 500],
+    ];
+    const VERTEX_DATA: [u8; 85] = [
+        0xa0, 0x01, 0x3f, 0x00, 0x00, 0x00, 0x58, 0x57, 0x58, 0x01, 0x26, 0x00, 0x00, 0x00, 0x01,
+        0x0c, 0x00, 0x00, 0x00, 0x58, 0x01, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01,
+        0x3f, 0x00, 0x00, 0x00, 0x17, 0x18, 0x17, 0x01, 0x26, 0x00, 0x00, 0x00, 0x01, 0x0c, 0x00,
+        0x00, 0x00, 0x17, 0x01, 0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    const INDICES: [u32; 12] = [0, 1, 2, 2, 1, 3, 4, 6, 5, 7, 8, 9];
+    const INDEX_DATA: [u8; 27] = [
+        0xe0, 0xf0, 0x10, 0xfe, 0xff, 0xf0, 0x0c, 0xff, 0x02, 0x02, 0x02, 0x00, 0x76, 0x87, 0x56,
+        0x67, 0x78, 0xa9, 0x86, 0x65, 0x89, 0x68, 0x98, 0x01, 0x69, 0x00, 0x00,
+    ];
+
+    const SEQUENCE: [u32; 6] = [0, 1, 51, 2, 49, 1000];
+    const SEQUENCE_DATA: [u8; 13] = [
+        0xd1, 0x00, 0x04, 0xcd, 0x01, 0x04, 0x07, 0x98, 0x1f, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    fn u16_bytes(values: &[u16]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    fn u32_bytes(values: &[u32]) -> Vec<u8> {
+        values
+            .iter()
+            .flat_map(|value| value.to_le_bytes())
+            .collect()
+    }
+
+    // The generator the larger fixtures were made with
+    struct Random(u32);
+
+    impl Random {
+        fn next(&mut self) -> u32 {
+            self.0 = self.0.wrapping_mul(1103515245).wrapping_add(12345);
+            self.0 >> 16
+        }
+    }
+
+    #[test]
+    fn attributes() {
+        let expected = u16_bytes(VERTICES.as_flattened());
+        assert_eq!(decode_attributes(&VERTEX_DATA, 4, 12).unwrap(), expected);
+        assert!(decode_attributes(&VERTEX_DATA, 4, 8).is_err());
+        assert!(decode_attributes(&VERTEX_DATA, 4, 10).is_err());
+        assert!(decode_attributes(&VERTEX_DATA[..84], 4, 12).is_err());
+        assert!(decode_attributes(&[0xa1; 85], 4, 12).is_err());
+    }
+
+    // 500 vertices over two blocks, with bytes that are constant, counting,
+    // slowly changing, quickly changing and random, so deltas of every width
+    #[test]
+    fn attribute_blocks() {
+        let mut random = Random(1);
+        let mut expected = Vec::new();
+        for i in 0..500usize {
+            for k in 0..16 {
+                expected.push(match k % 4 {
+                    0 => 7,
+                    1 => (i + k) as u8,
+                    2 => (i * i / 50 + (random.next() % 5) as usize) as u8,
+                    _ if k < 8 => (i * 3 + (random.next() % 24) as usize) as u8,
+                    _ => random.next() as u8,
+                });
+            }
+        }
+        let data = include_bytes!("../testdata/meshopt/attributes.bin");
+        assert_eq!(decode_attributes(data, 500, 16).unwrap(), expected);
+    }
+
+    #[test]
+    fn triangles() {
+        assert_eq!(decode_triangles(&INDEX_DATA, 12).unwrap(), INDICES);
+        assert!(decode_triangles(&INDEX_DATA, 11).is_err());
+        assert!(decode_triangles(&INDEX_DATA[..26], 12).is_err());
+        let mut trailing = INDEX_DATA.to_vec();
+        trailing.insert(10, 0);
+        assert!(decode_triangles(&trailing, 12).is_err());
+        let mut version = INDEX_DATA;
+        version[0] = 0xe2;
+        assert!(decode_triangles(&version, 12).is_err());
+    }
+
+    // An 8 by 8 grid and then triangles between random vertices, which need
+    // most of their indices stored explicitly
+    #[test]
+    fn scattered_triangles() {
+        let mut expected = Vec::new();
+        for y in 0..8 {
+            for x in 0..8 {
+                let i = y * 9 + x;
+                expected.extend_from_slice(&[i, i + 9, i + 1, i + 1, i + 9, i + 10]);
+            }
+        }
+        let mut random = Random(1);
+        expected.extend((0..120).map(|_| random.next() % 1000));
+
+        let data = include_bytes!("../testdata/meshopt/triangles.bin");
+        let indices = decode_triangles(data, expected.len()).unwrap();
+        assert_eq!(indices.len(), expected.len());
+        // The encoder rotates triangles, but keeps their winding
+        for (triangle, expected) in indices.chunks_exact(3).zip(expected.chunks_exact(3)) {
+            let rotations = [0, 1, 2].map(|r| [0, 1, 2].map(|i| expected[(i + r) % 3]));
+            assert!(rotations.contains(&[triangle[0], triangle[1], triangle[2]]));
+        }
+    }
+
+    // Version 1 uses codes 13 and 14 for one less or one more than the last
+    // explicitly stored index, rather than for the vertex FIFO
+    #[test]
+    fn triangles_version_1() {
+        let mut data = vec![0xe1, 0xf0, 0x1f, 0x0e, 0x0d, 0x14];
+        data.extend_from_slice(&INDEX_DATA[11..]);
+        assert_eq!(
+            decode_triangles(&data, 12).unwrap(),
+            [0, 1, 2, 2, 1, 10, 2, 10, 11, 2, 11, 10]
+        );
+        data[0] = 0xe0;
+        assert_ne!(
+            decode_triangles(&data, 12).unwrap(),
+            [0, 1, 2, 2, 1, 10, 2, 10, 11, 2, 11, 10]
+        );
+    }
+
+    #[test]
+    fn indices() {
+        assert_eq!(decode_indices(&SEQUENCE_DATA, 6).unwrap(), SEQUENCE);
+        assert!(decode_indices(&SEQUENCE_DATA, 7).is_err());
+        assert!(decode_indices(&SEQUENCE_DATA, 5).is_err());
+        let mut version = SEQUENCE_DATA;
+        version[0] = 0xd2;
+        assert!(decode_indices(&version, 6).is_err());
+    }
+
+    #[test]
+    fn octahedral_filter() {
+        let mut data = vec![
+            0, 1, 127, 0, 0, 187, 127, 1, 255, 1, 127, 0, 14, 130, 127, 1,
+        ];
+        octahedral(&mut data, 4).unwrap();
+        assert_eq!(
+            data,
+            [0, 1, 127, 0, 0, 159, 82, 1, 255, 1, 127, 0, 1, 130, 241, 1]
+        );
+
+        let mut data = u16_bytes(&[
+            0, 1, 2047, 0, 0, 1870, 2047, 1, 2017, 1, 2047, 0, 14, 1300, 2047, 1,
+        ]);
+        octahedral(&mut data, 8).unwrap();
+        assert_eq!(
+            data,
+            u16_bytes(&[
+                0, 16, 32767, 0, 0, 32621, 3088, 1, 32764, 16, 471, 0, 307, 28541, 16093, 1,
+            ])
+        );
+        assert!(octahedral(&mut data, 16).is_err());
+    }
+
+    #[test]
+    fn quaternion_filter() {
+        let mut data = u16_bytes(&[
+            0, 1, 0, 0x7fc, 0, 1870, 0, 0x7fd, 2017, 1, 0, 0x7fe, 14, 1300, 0, 0x7ff,
+        ]);
+        quaternion(&mut data, 8).unwrap();
+        assert_eq!(
+            data,
+            u16_bytes(&[
+                32767, 0, 11, 0, 0, 25013, 0, 21166, 11, 0, 23504, 22830, 158, 14715, 0, 29277,
+            ])
+        );
+        assert!(quaternion(&mut data, 4).is_err());
+    }
+
+    #[test]
+    fn exponential_filter() {
+        let mut data = u32_bytes(&[0, 0xff000003, 0x02fffff7, 0xfe7fffff]);
+        exponential(&mut data, 16).unwrap();
+        assert_eq!(data, u32_bytes(&[0, 0x3fc00000, 0xc2100000, 0x49fffffe]));
+        assert!(exponential(&mut data, 6).is_err());
+    }
+
+    fn compressed(
+        mode: &str,
+        filter: &str,
+        data: &[u8],
+        stride: usize,
+        count: usize,
+    ) -> Result<Glb> {
+        let json = json!({
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["EXT_meshopt_compression"],
+            "extensionsRequired": ["EXT_meshopt_compression"],
+            "buffers": [
+                {
+                    "uri": format!("data:application/octet-stream;base64,{}", STANDARD.encode(data)),
+                    "byteLength": data.len(),
+                },
+                {
+                    "byteLength": stride * count,
+                    "extensions": {"EXT_meshopt_compression": {"fallback": true}},
+                },
+            ],
+            "bufferViews": [{
+                "buffer": 1,
+                "byteLength": stride * count,
+                "extensions": {"EXT_meshopt_compression": {
+                    "buffer": 0,
+                    "byteLength": data.len(),
+                    "byteStride": stride,
+                    "count": count,
+                    "mode": mode,
+                    "filter": filter,
+                }},
+            }],
+        });
+        Glb::load_gltf(json.to_string().as_bytes(), "")
+    }
+
+    #[test]
+    fn decompressed_buffer_views() {
+        let glb = compressed("INDICES", "NONE", &SEQUENCE_DATA, 4, 6).unwrap();
+        assert_eq!(glb.buffer_view(0).unwrap().1, u32_bytes(&SEQUENCE));
+
+        let glb = compressed("TRIANGLES", "NONE", &INDEX_DATA, 2, 12).unwrap();
+        assert_eq!(
+            glb.buffer_view(0).unwrap().1,
+            u16_bytes(&INDICES.map(|index| index as u16))
+        );
+
+        let glb = compressed("ATTRIBUTES", "NONE", &VERTEX_DATA, 12, 4).unwrap();
+        let mut expected = u16_bytes(VERTICES.as_flattened());
+        assert_eq!(glb.buffer_view(0).unwrap().1, expected);
+
+        let glb = compressed("ATTRIBUTES", "EXPONENTIAL", &VERTEX_DATA, 12, 4).unwrap();
+        exponential(&mut expected, 12).unwrap();
+        assert_eq!(glb.buffer_view(0).unwrap().1, expected);
+    }
+
+    #[test]
+    fn filters_only_apply_to_attributes() {
+        assert!(matches!(
+            compressed("INDICES", "OCTAHEDRAL", &SEQUENCE_DATA, 4, 6),
+            Err(Error::Meshopt("filters only apply to attributes"))
+        ));
+        assert!(matches!(
+            compressed("INDICES", "NONE", &SEQUENCE_DATA, 8, 6),
+            Err(Error::Meshopt("index stride isn't 2 or 4"))
+        ));
+    }
+
+    #[test]
+    fn oversized_fallback() {
+        let count = MAX_BUFFER_SIZE / 4 + 1;
+        assert!(matches!(
+            compressed("INDICES", "NONE", &SEQUENCE_DATA, 4, count),
+            Err(Error::TooLarge {
+                size,
+                max: MAX_BUFFER_SIZE,
+            }) if size == count * 4
+        ));
+    }
+
+    #[test]
+    fn oversized_fallbacks_together() {
+        // Each one fits, but not alongside the others
+        let length = MAX_BUFFER_SIZE / 2 + 1;
+        let fallback = json!({
+            "byteLength": length,
+            "extensions": {"EXT_meshopt_compression": {"fallback": true}},
+        });
+        let json = json!({
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["EXT_meshopt_compression"],
+            "buffers": [fallback, fallback, fallback],
+        });
+        assert!(matches!(
+            Glb::load_gltf(json.to_string().as_bytes(), ""),
+            Err(Error::TooLarge {
+                size,
+                max: MAX_BUFFER_SIZE,
+            }) if size == length * 3
+        ));
+    }
+}
@@ -0,0 +1,203 @@
+use glam::{Vec3, Vec4};
+
+use crate::{get, Accessor, Buffers, Error, MeshPrimitive, Result};
+
+/// The morphable attributes of a primitive, each empty if it doesn't have
+/// them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Vertices {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub tangents: Vec<Vec4>,
+}
+
+/// Per vertex displacements, each empty if the target doesn't move that
+/// attribute. Tangent displacements leave the handedness alone.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MorphTarget {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub tangents: Vec<Vec3>,
+}
+
+/// A primitive's base attributes and morph targets, read out of the
+/// buffers so they can be blended every frame.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Morph {
+    pub base: Vertices,
+    pub targets: Vec<MorphTarget>,
+}
+
+fn check<T>(items: Vec<T>, count: usize) -> Result<Vec<T>> {
+    match items.len() {
+        0 => Ok(items),
+        found if found == count => Ok(items),
+        found => Err(Error::Count {
+            expected: count,
+            found,
+        }),
+    }
+}
+
+fn read_vec3(glb: &impl Buffers, accessor: Option<&Accessor>, count: usize) -> Result<Vec<Vec3>> {
+    check(
+        accessor
+            .map(|accessor| accessor.read_vec3_f32(glb))
+            .transpose()?
+            .unwrap_or_default(),
+        count,
+    )
+}
+
+impl Morph {
+    pub fn load(glb: &impl Buffers, primitive: &MeshPrimitive) -> Result<Self> {
+        let positions = primitive
+            .attribute(glb, "POSITION")?
+            .map(|accessor| accessor.read_vec3_f32(glb))
+            .transpose()?
+            .unwrap_or_default();
+        let count = positions.len();
+        let base = Vertices {
+            positions,
+            normals: read_vec3(glb, primitive.attribute(glb, "NORMAL")?, count)?,
+            tangents: check(
+                primitive
+                    .attribute(glb, "TANGENT")?
+                    .map(|accessor| accessor.read_vec4(glb))
+                    .transpose()?
+                    .unwrap_or_default(),
+                count,
+            )?,
+        };
+
+        let targets = primitive
+            .targets
+            .iter()
+            .map(|target| {
+                let attribute = |name: &str| {
+                    target
+                        .get(name)
+                        .map(|index| get(&glb.gltf().accessors, "accessor", *index))
+                        .transpose()
+                };
+                Ok(MorphTarget {
+                    positions: read_vec3(glb, attribute("POSITION")?, count)?,
+                    normals: read_vec3(glb, attribute("NORMAL")?, count)?,
+                    tangents: read_vec3(glb, attribute("TANGENT")?, count)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self { base, targets })
+    }
+
+    /// The base attributes with every target added in proportion to its
+    /// weight, such as those in a sampled `Pose`. Missing weights count as
+    /// zero.
+    pub fn apply(&self, weights: &[f32]) -> Vertices {
+        let mut vertices = self.base.clone();
+        for (target, weight) in self.targets.iter().zip(weights) {
+            if *weight == 0.0 {
+                continue;
+            }
+            for (position, delta) in vertices.positions.iter_mut().zip(&target.positions) {
+                *position += *delta * *weight;
+            }
+            for (normal, delta) in vertices.normals.iter_mut().zip(&target.normals) {
+                *normal += *delta * *weight;
+            }
+            for (tangent, delta) in vertices.tangents.iter_mut().zip(&target.tangents) {
+                *tangent += (*delta * *weight).extend(0.0);
+            }
+        }
+
+        vertices
+            .normals
+            .iter_mut()
+            .for_each(|normal| *normal = normal.normalize_or_zero());
+        vertices.tangents.iter_mut().for_each(|tangent| {
+            *tangent = tangent.truncate().normalize_or_zero().extend(tangent.w)
+        });
+        vertices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{fixture, Glb};
+
+    fn glb(targets: impl Fn(&mut Glb) -> Value) -> Glb {
+        fixture::glb(|glb| {
+            json!({"meshes": [{"primitives": [{
+                "attributes": {
+                    "POSITION": glb.push_accessor(&[Vec3::ZERO, Vec3::X], None),
+                    "NORMAL": glb.push_accessor(&[Vec3::Z, Vec3::Z], None),
+                    "TANGENT": glb.push_accessor(&[Vec4::new(1.0, 0.0, 0.0, -1.0); 2], None),
+                },
+                "targets": targets(glb),
+            }]}]})
+        })
+    }
+
+    fn load(glb: &Glb) -> Result<Morph> {
+        Morph::load(glb, &glb.gltf.meshes[0].primitives[0])
+    }
+
+    fn two_targets() -> Glb {
+        glb(|glb| {
+            let up = glb.push_accessor(&[Vec3::Y, Vec3::Y], None);
+            let out = glb.push_accessor(&[Vec3::Z, Vec3::ZERO], None);
+            let tilt = glb.push_accessor(&[Vec3::X, Vec3::ZERO], None);
+            json!([{"POSITION": up}, {"POSITION": out, "NORMAL": tilt, "TANGENT": tilt}])
+        })
+    }
+
+    #[test]
+    fn weights_blend_targets() {
+        let morph = load(&two_targets()).unwrap();
+        assert_eq!(morph.targets.len(), 2);
+        // Attributes a target doesn't have are left empty
+        assert!(morph.targets[0].normals.is_empty());
+
+        let vertices = morph.apply(&[0.5, 2.0]);
+        assert_eq!(
+            vertices.positions,
+            [Vec3::new(0.0, 0.5, 2.0), Vec3::new(1.0, 0.5, 0.0)]
+        );
+        // Displaced normals and tangents are renormalized, and tangents keep
+        // their handedness
+        let diagonal = Vec3::new(2.0, 0.0, 1.0).normalize();
+        assert!(vertices.normals[0].abs_diff_eq(diagonal, 1e-6));
+        assert_eq!(vertices.normals[1], Vec3::Z);
+        assert!(vertices.tangents[0].abs_diff_eq(Vec4::new(1.0, 0.0, 0.0, -1.0), 1e-6));
+    }
+
+    #[test]
+    fn missing_weights_are_zero() {
+        let morph = load(&two_targets()).unwrap();
+        assert_eq!(morph.apply(&[]), morph.base);
+        assert_eq!(morph.apply(&[0.0, 0.0]), morph.base);
+        assert_eq!(
+            morph.apply(&[1.0]).positions,
+            [Vec3::Y, Vec3::new(1.0, 1.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn targets_need_a_value_per_vertex() {
+        let glb = glb(|glb| {
+            let short = glb.push_accessor(&[Vec3::Y], None);
+            json!([{"POSITION": short}])
+        });
+        assert!(matches!(
+            load(&glb),
+            Err(Error::Count {
+                expected: 2,
+                found: 1
+            })
+        ));
+    }
+}
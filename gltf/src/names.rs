@@ -0,0 +1,107 @@
+use crate::{Animation, Glb, Gltf, Material, Mesh, Node};
+
+// Names don't have to be unique, so each lookup finds the first object that
+// has the name
+macro_rules! by_name {
+    ($($method:ident, $field:ident, $ty:ty;)*) => {
+        impl Gltf {
+            $(
+                pub fn $method(&self, name: &str) -> Option<(usize, &$ty)> {
+                    self.$field
+                        .iter()
+                        .enumerate()
+                        .find(|(_, item)| item.name.as_deref() == Some(name))
+                }
+            )*
+        }
+
+        impl Glb {
+            $(
+                pub fn $method(&self, name: &str) -> Option<(usize, &$ty)> {
+                    self.gltf.$method(name)
+                }
+            )*
+        }
+    };
+}
+
+by_name! {
+    node_by_name, nodes, Node;
+    mesh_by_name, meshes, Mesh;
+    material_by_name, materials, Material;
+    animation_by_name, animations, Animation;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn gltf() -> Gltf {
+        let json = json!({
+            "asset": {"version": "2.0"},
+            "nodes": [{"name": "root"}, {}, {"name": "arm"}, {"name": "arm"}],
+            "meshes": [{"name": "body", "primitives": []}],
+            "materials": [{}, {"name": "skin"}],
+            "animations": [{"name": "walk", "channels": [], "samplers": []}],
+        });
+        Gltf::load(json.to_string().as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn lookups() {
+        let gltf = gltf();
+        assert_eq!(gltf.node_by_name("root").unwrap().0, 0);
+        assert_eq!(gltf.mesh_by_name("body").unwrap().0, 0);
+        assert_eq!(gltf.material_by_name("skin").unwrap().0, 1);
+        assert_eq!(gltf.animation_by_name("walk").unwrap().0, 0);
+        assert!(gltf.node_by_name("leg").is_none());
+        assert!(gltf.node_by_name("").is_none());
+        // Names are per kind of object
+        assert!(gltf.mesh_by_name("root").is_none());
+    }
+
+    #[test]
+    fn duplicates_find_the_first() {
+        let gltf = gltf();
+        let (i, node) = gltf.node_by_name("arm").unwrap();
+        assert_eq!(i, 2);
+        assert_eq!(node.name.as_deref(), Some("arm"));
+    }
+
+    #[test]
+    fn edits_are_found() {
+        let mut gltf = gltf();
+        gltf.nodes[0].name = Some("base".to_owned());
+        gltf.nodes[2].name = Some("leg".to_owned());
+        gltf.nodes
+            .push(serde_json::from_value(json!({"name": "hand"})).unwrap());
+        assert_eq!(gltf.node_by_name("base").unwrap().0, 0);
+        assert!(gltf.node_by_name("root").is_none());
+        // The other node still called arm
+        assert_eq!(gltf.node_by_name("arm").unwrap().0, 3);
+        assert_eq!(gltf.node_by_name("leg").unwrap().0, 2);
+        assert_eq!(gltf.node_by_name("hand").unwrap().0, 4);
+    }
+
+    #[test]
+    fn deserialized_gltfs() {
+        let gltf: Gltf = serde_json::from_value(json!({
+            "asset": {"version": "2.0"},
+            "nodes": [{}, {"name": "root"}],
+        }))
+        .unwrap();
+        assert_eq!(gltf.node_by_name("root").unwrap().0, 1);
+    }
+
+    #[test]
+    fn glbs_look_up_their_json() {
+        let mut glb = Glb::new();
+        glb.gltf = gltf();
+        let bytes = glb.to_bytes().unwrap();
+        let glb = Glb::load(&bytes).unwrap();
+        assert_eq!(glb.node_by_name("arm").unwrap().0, 2);
+        assert_eq!(glb.material_by_name("skin").unwrap().0, 1);
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::HashMap;
+
+use glam::{Vec2, Vec3, Vec4};
+
+use crate::{get, Error, Result};
+
+fn triangle<T: Copy>(data: &[T], triangle: &[u32]) -> Result<[T; 3]> {
+    let vertex = |i: usize| get(data, "vertex", triangle[i] as usize).copied();
+    Ok([vertex(0)?, vertex(1)?, vertex(2)?])
+}
+
+// Exports often split vertices along UV seams, so vertices are grouped by
+// value rather than by index to keep the shading continuous across them
+fn group<K: Eq + std::hash::Hash>(keys: impl Iterator<Item = K>) -> (Vec<usize>, usize) {
+    let mut groups = HashMap::new();
+    let ids = keys
+        .map(|key| {
+            let next = groups.len();
+            *groups.entry(key).or_insert(next)
+        })
+        .collect();
+    (ids, groups.len())
+}
+
+fn bits(v: Vec3) -> [u32; 3] {
+    v.to_array().map(f32::to_bits)
+}
+
+/// Copies out the vertex for each index, so every triangle has its own
+/// vertices. Use it on the other attributes alongside `flat_normals`.
+pub fn unweld<T: Copy>(data: &[T], indices: &[u32]) -> Result<Vec<T>> {
+    indices
+        .iter()
+        .map(|index| get(data, "vertex", *index as usize).copied())
+        .collect()
+}
+
+/// A face normal for each index of a triangle list, which is what the spec
+/// asks for when a primitive doesn't have normals.
+pub fn flat_normals(positions: &[Vec3], indices: &[u32]) -> Result<Vec<Vec3>> {
+    let mut normals = Vec::with_capacity(indices.len());
+    for indices in indices.chunks_exact(3) {
+        let [a, b, c] = triangle(positions, indices)?;
+        let normal = (b - a).cross(c - a).try_normalize().unwrap_or(Vec3::Z);
+        normals.extend_from_slice(&[normal; 3]);
+    }
+    Ok(normals)
+}
+
+/// Vertex normals averaged from the faces around each vertex, weighted by
+/// their area. Vertices in the same place share a normal.
+pub fn smooth_normals(positions: &[Vec3], indices: &[u32]) -> Result<Vec<Vec3>> {
+    let (ids, count) = group(positions.iter().map(|position| bits(*position)));
+    let mut sums = vec![Vec3::ZERO; count];
+    for indices in indices.chunks_exact(3) {
+        let [a, b, c] = triangle(positions, indices)?;
+        let normal = (b - a).cross(c - a);
+        for index in indices {
+            sums[ids[*index as usize]] += normal;
+        }
+    }
+    Ok(ids
+        .iter()
+        .map(|id| sums[*id].try_normalize().unwrap_or(Vec3::Z))
+        .collect())
+}
+
+/// Tangents from MikkTSpace, so normal maps baked with it shade without
+/// seams. MikkTSpace can give the corners of one vertex different tangents,
+/// so vertices are split where they disagree: `vertices` is the original
+/// vertex of each new one, for use with `unweld` on the other attributes,
+/// and `indices` replaces the primitive's indices.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tangents {
+    pub tangents: Vec<Vec4>,
+    pub vertices: Vec<u32>,
+    pub indices: Vec<u32>,
+}
+
+struct Geometry<'a> {
+    positions: &'a [Vec3],
+    normals: &'a [Vec3],
+    uvs: &'a [Vec2],
+    indices: &'a [u32],
+    corners: Vec<Vec4>,
+}
+
+impl Geometry<'_> {
+    fn vertex(&self, face: usize, vert: usize) -> usize {
+        self.indices[face * 3 + vert] as usize
+    }
+}
+
+impl bevy_mikktspace::Geometry for Geometry<'_> {
+    fn num_faces(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    fn num_vertices_of_face(&self, _face: usize) -> usize {
+        3
+    }
+
+    fn position(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.positions[self.vertex(face, vert)].to_array()
+    }
+
+    fn normal(&self, face: usize, vert: usize) -> [f32; 3] {
+        self.normals[self.vertex(face, vert)].to_array()
+    }
+
+    fn tex_coord(&self, face: usize, vert: usize) -> [f32; 2] {
+        // glTF has V pointing down the texture, and MikkTSpace has it up
+        let uv = self.uvs[self.vertex(face, vert)];
+        [uv.x, -uv.y]
+    }
+
+    fn set_tangent_encoded(&mut self, tangent: [f32; 4], face: usize, vert: usize) {
+        self.corners[face * 3 + vert] = Vec4::from_array(tangent);
+    }
+}
+
+/// Generates tangents for a triangle list. `w` is the handedness, so the
+/// bitangent is `normal.cross(tangent.xyz) * w`.
+pub fn tangents(
+    positions: &[Vec3],
+    normals: &[Vec3],
+    uvs: &[Vec2],
+    indices: &[u32],
+) -> Result<Tangents> {
+    let indices = &indices[..indices.len() / 3 * 3];
+    for index in indices {
+        let index = *index as usize;
+        get(positions, "vertex", index)?;
+        get(normals, "vertex", index)?;
+        get(uvs, "vertex", index)?;
+    }
+
+    let mut geometry = Geometry {
+        positions,
+        normals,
+        uvs,
+        indices,
+        corners: vec![Vec4::ZERO; indices.len()],
+    };
+    if !bevy_mikktspace::generate_tangents(&mut geometry) {
+        return Err(Error::Tangents);
+    }
+
+    let mut split = HashMap::new();
+    let mut tangents = Tangents {
+        tangents: Vec::new(),
+        vertices: Vec::new(),
+        indices: Vec::with_capacity(indices.len()),
+    };
+    for (index, tangent) in indices.iter().zip(geometry.corners) {
+        let key = (*index, tangent.to_array().map(f32::to_bits));
+        let vertex = *split.entry(key).or_insert_with(|| {
+            tangents.tangents.push(tangent);
+            tangents.vertices.push(*index);
+            tangents.vertices.len() as u32 - 1
+        });
+        tangents.indices.push(vertex);
+    }
+    Ok(tangents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A unit square facing +Z, with V pointing down the texture
+    const POSITIONS: [Vec3; 4] = [
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(1.0, 0.0, 0.0),
+        Vec3::new(1.0, 1.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0),
+    ];
+    const INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+    fn generate(uvs: [[f32; 2]; 4]) -> Tangents {
+        let uvs = uvs.map(Vec2::from);
+        tangents(&POSITIONS, &[Vec3::Z; 4], &uvs, &INDICES).unwrap()
+    }
+
+    // The tangents of the quad's vertices, which only works if none were split
+    fn quad(uvs: [[f32; 2]; 4]) -> Vec<Vec4> {
+        let tangents = generate(uvs);
+        assert_eq!(tangents.vertices, [0, 1, 2, 3]);
+        assert_eq!(tangents.indices, INDICES);
+        tangents.tangents
+    }
+
+    #[test]
+    fn quad_tangents() {
+        let tangents = quad([[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [0.0, 0.0]]);
+        assert_eq!(tangents, [Vec4::new(1.0, 0.0, 0.0, 1.0); 4]);
+        // The bitangent points up the texture
+        let bitangent = Vec3::Z.cross(tangents[0].truncate()) * tangents[0].w;
+        assert_eq!(bitangent, Vec3::Y);
+    }
+
+    #[test]
+    fn rotated_uvs() {
+        let tangents = quad([[0.0, 0.0], [0.0, 1.0], [1.0, 1.0], [1.0, 0.0]]);
+        for tangent in tangents {
+            assert!(tangent.abs_diff_eq(Vec4::new(0.0, 1.0, 0.0, 1.0), 1e-6));
+        }
+    }
+
+    #[test]
+    fn mirrored_uvs() {
+        let tangents = quad([[1.0, 1.0], [0.0, 1.0], [0.0, 0.0], [1.0, 0.0]]);
+        assert_eq!(tangents, [Vec4::new(-1.0, 0.0, 0.0, -1.0); 4]);
+        // Still pointing up the texture, despite the tangent flipping
+        let bitangent = Vec3::Z.cross(tangents[0].truncate()) * tangents[0].w;
+        assert_eq!(bitangent, Vec3::Y);
+    }
+
+    #[test]
+    fn degenerate_uvs() {
+        // Any tangent along the surface will do, and MikkTSpace picks the
+        // handedness
+        let tangents = quad([[0.5, 0.5]; 4]);
+        for tangent in tangents {
+            assert_eq!(tangent.truncate().dot(Vec3::Z), 0.0);
+            assert!(tangent.truncate().is_normalized());
+        }
+    }
+
+    #[test]
+    fn split_vertices() {
+        // The second triangle's UVs are mirrored, so the vertices it shares
+        // with the first need a tangent for each side
+        let tangents = generate([[0.0, 1.0], [1.0, 1.0], [1.0, 0.0], [1.0, 1.0]]);
+        assert_eq!(tangents.vertices, [0, 1, 2, 0, 2, 3]);
+        assert_eq!(tangents.indices, [0, 1, 2, 3, 4, 5]);
+        for (i, tangent) in tangents.tangents.iter().enumerate() {
+            let w = if i < 3 { 1.0 } else { -1.0 };
+            assert_eq!(tangent.w, w);
+        }
+    }
+
+    #[test]
+    fn missing_vertices() {
+        let result = tangents(&POSITIONS, &[Vec3::Z; 3], &[Vec2::ZERO; 4], &INDICES);
+        assert!(matches!(
+            result,
+            Err(Error::Index {
+                kind: "vertex",
+                index: 3
+            })
+        ));
+    }
+}
@@ -0,0 +1,465 @@
+use std::collections::HashMap;
+
+use bytemuck::Pod;
+use glam::Vec3;
+
+use crate::{Error, Result};
+
+/// The post-transform cache size ACMR is reported against, which is about
+/// what current GPUs behave like.
+pub const CACHE_SIZE: usize = 16;
+
+fn check(indices: &[u32], vertex_count: usize) -> Result<()> {
+    match indices
+        .iter()
+        .find(|index| **index as usize >= vertex_count)
+    {
+        Some(index) => Err(Error::Index {
+            kind: "vertex",
+            index: *index as usize,
+        }),
+        None => Ok(()),
+    }
+}
+
+// A FIFO post-transform cache. A vertex is in the cache if fewer than
+// `size` misses have happened since it was loaded, and since it was last
+// cleared
+struct Cache {
+    loaded: Vec<Option<usize>>,
+    size: usize,
+    misses: usize,
+    cleared: usize,
+}
+
+impl Cache {
+    fn new(vertex_count: usize, size: usize) -> Self {
+        Self {
+            loaded: vec![None; vertex_count],
+            size,
+            misses: 0,
+            cleared: 0,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.cleared = self.misses;
+    }
+
+    fn misses(&mut self, triangle: &[u32]) -> usize {
+        let before = self.misses;
+        for index in triangle {
+            let loaded = &mut self.loaded[*index as usize];
+            let cached =
+                loaded.is_some_and(|time| time >= self.cleared && self.misses - time < self.size);
+            if !cached {
+                *loaded = Some(self.misses);
+                self.misses += 1;
+            }
+        }
+        self.misses - before
+    }
+}
+
+/// Average cache misses per triangle for a FIFO cache of `cache_size`
+/// vertices. Lower is better, 0.5 is the best a regular grid can do and 3
+/// means no vertex is ever reused.
+pub fn acmr(indices: &[u32], cache_size: usize) -> f32 {
+    let triangles = indices.len() / 3;
+    if triangles == 0 {
+        return 0.0;
+    }
+    let vertex_count = indices.iter().max().map_or(0, |max| *max as usize + 1);
+    let mut cache = Cache::new(vertex_count, cache_size);
+    let misses: usize = indices
+        .chunks_exact(3)
+        .map(|triangle| cache.misses(triangle))
+        .sum();
+    misses as f32 / triangles as f32
+}
+
+/// Merges vertices with exactly the same bytes, returning the unique
+/// vertices and the indices into them.
+pub fn deduplicate<T: Pod>(vertices: &[T], indices: &[u32]) -> Result<(Vec<T>, Vec<u32>)> {
+    check(indices, vertices.len())?;
+    let mut unique = Vec::new();
+    let mut seen = HashMap::new();
+    let remap: Vec<u32> = vertices
+        .iter()
+        .map(|vertex| {
+            *seen.entry(bytemuck::bytes_of(vertex)).or_insert_with(|| {
+                unique.push(*vertex);
+                unique.len() as u32 - 1
+            })
+        })
+        .collect();
+    let indices = indices.iter().map(|index| remap[*index as usize]).collect();
+    Ok((unique, indices))
+}
+
+// Tom Forsyth's linear-speed vertex cache optimisation. Vertices score
+// higher the more recently they were used and the fewer triangles they
+// have left, and the best scoring triangle using a cached vertex goes next
+const FORSYTH_CACHE: usize = 32;
+
+fn vertex_score(position: Option<usize>, remaining: usize) -> f32 {
+    if remaining == 0 {
+        return -1.0;
+    }
+    let cache = match position {
+        // The last triangle's vertices score the same whichever order
+        // they're used in
+        Some(position) if position < 3 => 0.75,
+        Some(position) => (1.0 - (position - 3) as f32 / (FORSYTH_CACHE - 3) as f32).powf(1.5),
+        None => 0.0,
+    };
+    cache + 2.0 * (remaining as f32).powf(-0.5)
+}
+
+/// Reorders triangles so vertices are reused while they're still in the
+/// post-transform cache, which cuts down on vertex shader invocations.
+pub fn optimize_vertex_cache(indices: &[u32], vertex_count: usize) -> Result<Vec<u32>> {
+    check(indices, vertex_count)?;
+    let triangles: Vec<[u32; 3]> = indices
+        .chunks_exact(3)
+        .map(|triangle| [triangle[0], triangle[1], triangle[2]])
+        .collect();
+
+    // The triangles still to be drawn around each vertex
+    let mut adjacent = vec![Vec::new(); vertex_count];
+    for (i, triangle) in triangles.iter().enumerate() {
+        for vertex in triangle {
+            adjacent[*vertex as usize].push(i);
+        }
+    }
+    let mut scores: Vec<f32> = adjacent
+        .iter()
+        .map(|triangles| vertex_score(None, triangles.len()))
+        .collect();
+
+    let mut emitted = vec![false; triangles.len()];
+    let mut cache: Vec<u32> = Vec::with_capacity(FORSYTH_CACHE + 3);
+    let mut output = Vec::with_capacity(triangles.len() * 3);
+    let mut best = None;
+    let mut next = 0;
+    while output.len() < triangles.len() * 3 {
+        // Nothing in the cache has triangles left, so carry on from the
+        // first one that hasn't been drawn
+        let current = match best {
+            Some(best) => best,
+            None => {
+                while emitted[next] {
+                    next += 1;
+                }
+                next
+            }
+        };
+        let triangle = triangles[current];
+        emitted[current] = true;
+        output.extend_from_slice(&triangle);
+
+        for vertex in triangle {
+            let adjacent = &mut adjacent[vertex as usize];
+            if let Some(i) = adjacent.iter().position(|i| *i == current) {
+                adjacent.swap_remove(i);
+            }
+        }
+        let mut updated = triangle.to_vec();
+        updated.extend(cache.iter().filter(|vertex| !triangle.contains(vertex)));
+        for evicted in updated.drain(FORSYTH_CACHE.min(updated.len())..) {
+            scores[evicted as usize] = vertex_score(None, adjacent[evicted as usize].len());
+        }
+        cache = updated;
+
+        for (position, vertex) in cache.iter().enumerate() {
+            let vertex = *vertex as usize;
+            scores[vertex] = vertex_score(Some(position), adjacent[vertex].len());
+        }
+        // Only triangles around cached vertices have had their scores
+        // change, and one of them is the best unless the cache is done with
+        best = None;
+        let mut best_score = f32::NEG_INFINITY;
+        for vertex in &cache {
+            for i in &adjacent[*vertex as usize] {
+                let score: f32 = triangles[*i]
+                    .iter()
+                    .map(|vertex| scores[*vertex as usize])
+                    .sum();
+                if score > best_score {
+                    best = Some(*i);
+                    best_score = score;
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Reorders triangles so the ones facing outwards are drawn first and hide
+/// more of the rest. They're moved in clusters that are each kept within
+/// `threshold` times their old vertex cache efficiency, so the whole mesh
+/// ends up about that much worse at most. Run it after
+/// `optimize_vertex_cache`, 1.05 is a reasonable threshold.
+pub fn optimize_overdraw(indices: &[u32], positions: &[Vec3], threshold: f32) -> Result<Vec<u32>> {
+    check(indices, positions.len())?;
+    let triangles = indices.len() / 3;
+    if triangles == 0 {
+        return Ok(Vec::new());
+    }
+    let triangle = |i: usize| &indices[i * 3..i * 3 + 3];
+
+    // Anywhere the cache has had to load all three vertices, the order
+    // before and after doesn't matter to it
+    let mut cache = Cache::new(positions.len(), CACHE_SIZE);
+    let mut starts = vec![0];
+    for i in 0..triangles {
+        if cache.misses(triangle(i)) == 3 && i > 0 {
+            starts.push(i);
+        }
+    }
+    starts.push(triangles);
+
+    // Those clusters are split further wherever the part so far is within
+    // the threshold of the whole cluster's efficiency, with the cache
+    // starting out empty for every part
+    let mut clusters = Vec::new();
+    for range in starts.windows(2) {
+        let (start, end) = (range[0], range[1]);
+        let limit = threshold * acmr(&indices[start * 3..end * 3], CACHE_SIZE);
+        cache.clear();
+        let mut part = (start, 0);
+        for i in start..end {
+            part.1 += cache.misses(triangle(i));
+            if part.1 as f32 <= limit * (i + 1 - part.0) as f32 || i + 1 == end {
+                clusters.push(part.0..i + 1);
+                part = (i + 1, 0);
+                cache.clear();
+            }
+        }
+    }
+
+    let corners = |i: usize| triangle(i).iter().map(|index| positions[*index as usize]);
+    let centre = (0..triangles).flat_map(corners).sum::<Vec3>() / (triangles * 3) as f32;
+    let mut keyed: Vec<(f32, std::ops::Range<usize>)> = clusters
+        .into_iter()
+        .map(|cluster| {
+            let (mut centroid, mut normal) = (Vec3::ZERO, Vec3::ZERO);
+            for i in cluster.clone() {
+                let [a, b, c] = [0, 1, 2].map(|k| positions[triangle(i)[k] as usize]);
+                centroid += a + b + c;
+                normal += (b - a).cross(c - a);
+            }
+            let centroid = centroid / (cluster.len() * 3) as f32;
+            ((centroid - centre).dot(normal.normalize_or_zero()), cluster)
+        })
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+
+    Ok(keyed
+        .into_iter()
+        .flat_map(|(_, cluster)| indices[cluster.start * 3..cluster.end * 3].iter().copied())
+        .collect())
+}
+
+/// Reorders vertices into the order the triangles first use them, so they
+/// get fetched from memory mostly in order. Unused vertices are dropped.
+pub fn optimize_vertex_fetch<T: Copy>(
+    vertices: &[T],
+    indices: &[u32],
+) -> Result<(Vec<T>, Vec<u32>)> {
+    check(indices, vertices.len())?;
+    let mut remap = vec![None; vertices.len()];
+    let mut reordered = Vec::new();
+    let indices = indices
+        .iter()
+        .map(|index| {
+            *remap[*index as usize].get_or_insert_with(|| {
+                reordered.push(vertices[*index as usize]);
+                reordered.len() as u32 - 1
+            })
+        })
+        .collect();
+    Ok((reordered, indices))
+}
+
+/// A mesh after `optimize`, with the ACMR it started and ended with.
+#[derive(Clone, Debug)]
+pub struct Optimized<T> {
+    pub vertices: Vec<T>,
+    pub indices: Vec<u32>,
+    pub acmr_before: f32,
+    pub acmr_after: f32,
+}
+
+/// Runs every optimisation on an indexed triangle list, in the order they
+/// work best in.
+pub fn optimize<T: Pod>(
+    vertices: &[T],
+    indices: &[u32],
+    position: impl Fn(&T) -> Vec3,
+) -> Result<Optimized<T>> {
+    let acmr_before = acmr(indices, CACHE_SIZE);
+    let (vertices, indices) = deduplicate(vertices, indices)?;
+    let indices = optimize_vertex_cache(&indices, vertices.len())?;
+    let positions: Vec<Vec3> = vertices.iter().map(position).collect();
+    let indices = optimize_overdraw(&indices, &positions, 1.05)?;
+    let (vertices, indices) = optimize_vertex_fetch(&vertices, &indices)?;
+    Ok(Optimized {
+        acmr_after: acmr(&indices, CACHE_SIZE),
+        vertices,
+        indices,
+        acmr_before,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A bumpy grid of `n` by `n` vertices, with its quads in rows
+    fn grid(n: u32) -> (Vec<Vec3>, Vec<u32>) {
+        let positions = (0..n * n)
+            .map(|i| {
+                let (x, y) = ((i % n) as f32, (i / n) as f32);
+                Vec3::new(x, y, (x * 0.7).sin() + (y * 0.4).cos())
+            })
+            .collect();
+        let mut indices = Vec::new();
+        for y in 0..n - 1 {
+            for x in 0..n - 1 {
+                let i = y * n + x;
+                indices.extend_from_slice(&[i, i + n, i + 1, i + 1, i + n, i + n + 1]);
+            }
+        }
+        (positions, indices)
+    }
+
+    // Shuffles the triangles, but not the corners within them
+    fn shuffle(indices: &[u32]) -> Vec<u32> {
+        let mut triangles: Vec<&[u32]> = indices.chunks_exact(3).collect();
+        let mut state = 1u32;
+        for i in (1..triangles.len()).rev() {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            triangles.swap(i, (state >> 16) as usize % (i + 1));
+        }
+        triangles.concat()
+    }
+
+    // Each triangle by its corners' positions, rotated to start from the
+    // smallest so only the winding is left to compare
+    fn triangles(positions: &[Vec3], indices: &[u32]) -> Vec<[[u32; 3]; 3]> {
+        let mut triangles: Vec<_> = indices
+            .chunks_exact(3)
+            .map(|triangle| {
+                let corners =
+                    [0, 1, 2].map(|i| positions[triangle[i] as usize].to_array().map(f32::to_bits));
+                let first = (0..3).min_by_key(|i| corners[*i]).unwrap();
+                [0, 1, 2].map(|i| corners[(first + i) % 3])
+            })
+            .collect();
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn hand_computed_acmr() {
+        // A 3 by 3 grid, which needs 9 misses for 8 triangles when the
+        // cache holds everything
+        let (_, indices) = grid(3);
+        assert_eq!(acmr(&indices, 16), 9.0 / 8.0);
+        // With room for a single triangle, starting the second row reloads
+        // two vertices from the first, as does the last quad
+        assert_eq!(acmr(&indices, 3), 12.0 / 8.0);
+        // A single quad
+        assert_eq!(acmr(&[0, 1, 2, 2, 1, 3], 16), 2.0);
+        // Nothing shared
+        assert_eq!(acmr(&[0, 1, 2, 3, 4, 5], 16), 3.0);
+        assert_eq!(acmr(&[], 16), 0.0);
+    }
+
+    #[test]
+    fn deduplicate_keeps_triangles() {
+        let (positions, indices) = grid(6);
+        // Every corner gets its own vertex
+        let unwelded: Vec<Vec3> = indices.iter().map(|i| positions[*i as usize]).collect();
+        let sequential: Vec<u32> = (0..indices.len() as u32).collect();
+
+        let (vertices, deduplicated) = deduplicate(&unwelded, &sequential).unwrap();
+        assert_eq!(vertices.len(), positions.len());
+        assert_eq!(
+            triangles(&vertices, &deduplicated),
+            triangles(&positions, &indices)
+        );
+        assert!(matches!(
+            deduplicate(&positions, &[0, 1, 36]),
+            Err(Error::Index {
+                kind: "vertex",
+                index: 36
+            })
+        ));
+    }
+
+    #[test]
+    fn vertex_cache_keeps_triangles() {
+        let (positions, indices) = grid(16);
+        let shuffled = shuffle(&indices);
+        let optimized = optimize_vertex_cache(&shuffled, positions.len()).unwrap();
+        assert_eq!(
+            triangles(&positions, &optimized),
+            triangles(&positions, &indices)
+        );
+        assert!(acmr(&optimized, CACHE_SIZE) < acmr(&shuffled, CACHE_SIZE));
+        assert!(optimize_vertex_cache(&[0, 1, 2], 2).is_err());
+    }
+
+    #[test]
+    fn overdraw_keeps_triangles() {
+        let (positions, indices) = grid(16);
+        let cached = optimize_vertex_cache(&indices, positions.len()).unwrap();
+        let optimized = optimize_overdraw(&cached, &positions, 1.05).unwrap();
+        assert_eq!(
+            triangles(&positions, &optimized),
+            triangles(&positions, &indices)
+        );
+        // The threshold holds within each cluster, and the cache carrying
+        // over between clusters costs a little more
+        assert!(acmr(&optimized, CACHE_SIZE) <= acmr(&cached, CACHE_SIZE) * 1.1);
+        assert!(optimize_overdraw(&[], &positions, 1.05).unwrap().is_empty());
+    }
+
+    #[test]
+    fn vertex_fetch_keeps_triangles() {
+        let (mut positions, indices) = grid(8);
+        // An unused vertex, which gets dropped
+        positions.push(Vec3::splat(100.0));
+        let shuffled = shuffle(&indices);
+        let (vertices, optimized) = optimize_vertex_fetch(&positions, &shuffled).unwrap();
+        assert_eq!(vertices.len(), 64);
+        assert_eq!(
+            triangles(&vertices, &optimized),
+            triangles(&positions, &indices)
+        );
+
+        // Vertices are numbered in the order they're first used
+        let mut next = 0;
+        for index in optimized {
+            assert!(index <= next);
+            next = next.max(index + 1);
+        }
+    }
+
+    #[test]
+    fn optimize_improves_acmr() {
+        let (positions, indices) = grid(16);
+        for indices in [indices.clone(), shuffle(&indices)] {
+            let optimized = optimize(&positions, &indices, |position| *position).unwrap();
+            assert_eq!(optimized.acmr_before, acmr(&indices, CACHE_SIZE));
+            assert_eq!(optimized.acmr_after, acmr(&optimized.indices, CACHE_SIZE));
+            assert!(optimized.acmr_after <= optimized.acmr_before);
+            assert_eq!(
+                triangles(&optimized.vertices, &optimized.indices),
+                triangles(&positions, &indices)
+            );
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use png::{ColorType, Decoder, Limits, Transformations};
+
+use crate::{image::MAX_SIZE, Error, Pixels, Result};
+
+pub(crate) const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+fn error(e: png::DecodingError) -> Error {
+    Error::Png(e.to_string())
+}
+
+pub(crate) fn decode(bytes: &[u8]) -> Result<Pixels> {
+    // The decoder only inflates as much data as the IHDR says the image
+    // needs, and the limit stops the IHDR asking for too much
+    let mut decoder = Decoder::new_with_limits(bytes, Limits { bytes: MAX_SIZE });
+    // Palettes, low bit depths and tRNS are expanded out, 16 bit samples
+    // keep their high byte, and anything without alpha gets it
+    decoder.set_transformations(Transformations::normalize_to_color8() | Transformations::ALPHA);
+    let mut reader = decoder.read_info().map_err(error)?;
+    let size = reader.output_buffer_size();
+    if size > MAX_SIZE {
+        return Err(Error::Png(format!(
+            "image is too big, it needs {size} bytes"
+        )));
+    }
+
+    let mut data = vec![0; size];
+    let info = reader.next_frame(&mut data).map_err(error)?;
+    data.truncate(info.buffer_size());
+    let data = match info.color_type {
+        ColorType::Rgba => data,
+        ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        ty => return Err(Error::Png(format!("unexpected {ty:?} output"))),
+    };
+
+    Ok(Pixels {
+        width: info.width,
+        height: info.height,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The patterns testdata/images was generated with
+    fn r(x: u32, y: u32) -> u8 {
+        (x * 29 + y * 3) as u8
+    }
+    fn g(x: u32, y: u32) -> u8 {
+        (y * 37 + x) as u8
+    }
+    fn b(x: u32, y: u32) -> u8 {
+        (x * y * 11) as u8
+    }
+    fn a(x: u32, y: u32) -> u8 {
+        ((x + y) * 13 + 40) as u8
+    }
+
+    fn check(pixels: &Pixels, width: u32, height: u32, expected: impl Fn(u32, u32) -> [u8; 4]) {
+        assert_eq!((pixels.width, pixels.height), (width, height));
+        for y in 0..height {
+            for x in 0..width {
+                let i = ((y * width + x) * 4) as usize;
+                assert_eq!(pixels.data[i..i + 4], expected(x, y), "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn palette_with_transparency() {
+        let colours = [
+            [255, 0, 0, 0],
+            [0, 255, 0, 128],
+            [0, 0, 255, 255],
+            [255, 255, 255, 255],
+        ];
+        let pixels = decode(include_bytes!("../testdata/images/palette_trns.png")).unwrap();
+        check(&pixels, 7, 3, |x, y| colours[((x + 2 * y) % 4) as usize]);
+    }
+
+    #[test]
+    fn transparent_grey() {
+        let pixels = decode(include_bytes!("../testdata/images/grey_trns.png")).unwrap();
+        check(&pixels, 6, 2, |x, y| {
+            let grey = (x * 32 + y) as u8;
+            [grey, grey, grey, if grey == 64 { 0 } else { 255 }]
+        });
+    }
+
+    #[test]
+    fn sixteen_bit() {
+        let pixels = decode(include_bytes!("../testdata/images/rgba16.png")).unwrap();
+        check(&pixels, 5, 4, |x, y| [r(x, y), g(x, y), b(x, y), a(x, y)]);
+    }
+
+    #[test]
+    fn invalid() {
+        let image = include_bytes!("../testdata/images/rgba16.png");
+        assert!(matches!(
+            decode(&image[..image.len() - 20]),
+            Err(Error::Png(_))
+        ));
+        assert!(matches!(decode(&image[..20]), Err(Error::Png(_))));
+        // 30000 by 30000 with hardly any data
+        assert!(matches!(
+            decode(include_bytes!("../testdata/images/huge.png")),
+            Err(Error::Png(e)) if e.contains("too big")
+        ));
+    }
+}
@@ -0,0 +1,427 @@
+use glam::{Mat4, Quat, Vec3};
+
+use crate::{get, Camera, Error, Gltf, Light, Node, Result};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub scale: Vec3,
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: Vec3::ZERO,
+            rotation: Quat::IDENTITY,
+            scale: Vec3::ONE,
+        }
+    }
+}
+
+impl Transform {
+    pub fn matrix(&self) -> Mat4 {
+        Mat4::from_scale_rotation_translation(self.scale, self.rotation, self.translation)
+    }
+}
+
+impl Node {
+    /// The node's transform relative to its parent. A `matrix` wins over
+    /// TRS properties, although the spec doesn't allow both.
+    pub fn local_matrix(&self) -> Mat4 {
+        match self.matrix {
+            Some(matrix) => Mat4::from_cols_array(&matrix),
+            None => self.transform().matrix(),
+        }
+    }
+
+    /// The node's transform split into TRS, decomposing `matrix` if needed.
+    pub fn transform(&self) -> Transform {
+        if let Some(matrix) = self.matrix {
+            let (scale, rotation, translation) =
+                Mat4::from_cols_array(&matrix).to_scale_rotation_translation();
+            return Transform {
+                translation,
+                rotation,
+                scale,
+            };
+        }
+
+        let default = Transform::default();
+        Transform {
+            translation: self
+                .translation
+                .map(Vec3::from_array)
+                .unwrap_or(default.translation),
+            rotation: self
+                .rotation
+                .map(Quat::from_array)
+                .unwrap_or(default.rotation),
+            scale: self.scale.map(Vec3::from_array).unwrap_or(default.scale),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SceneNode<'a> {
+    pub index: usize,
+    pub node: &'a Node,
+    pub parent: Option<usize>,
+    pub depth: usize,
+    pub local: Mat4,
+    pub world: Mat4,
+    pub camera: Option<&'a Camera>,
+    pub light: Option<&'a Light>,
+}
+
+impl SceneNode<'_> {
+    pub fn position(&self) -> Vec3 {
+        self.world.w_axis.truncate()
+    }
+
+    /// The way cameras and lights on the node face, which is down -Z.
+    pub fn forward(&self) -> Vec3 {
+        self.world
+            .transform_vector3(Vec3::NEG_Z)
+            .normalize_or_zero()
+    }
+}
+
+impl Gltf {
+    /// Walks the default scene, see `traverse_scene`.
+    pub fn traverse(&self) -> Result<Vec<SceneNode<'_>>> {
+        if self.scenes.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.traverse_scene(self.scene)
+    }
+
+    /// Every node in a scene, depth first with parents before their
+    /// children, along with their accumulated world transforms.
+    pub fn traverse_scene(&self, scene: usize) -> Result<Vec<SceneNode<'_>>> {
+        self.walk(scene, |_, node| node.local_matrix())
+    }
+
+    /// World transforms of every node in the default scene, indexed by node.
+    /// Nodes outside of the scene are left at the identity.
+    pub fn world_matrices(&self) -> Result<Vec<Mat4>> {
+        self.world_matrices_with(|_, node| node.local_matrix())
+    }
+
+    pub(crate) fn world_matrices_with(
+        &self,
+        local: impl Fn(usize, &Node) -> Mat4,
+    ) -> Result<Vec<Mat4>> {
+        let mut world = vec![Mat4::IDENTITY; self.nodes.len()];
+        if !self.scenes.is_empty() {
+            for node in self.walk(self.scene, local)? {
+                world[node.index] = node.world;
+            }
+        }
+        Ok(world)
+    }
+
+    fn walk(
+        &self,
+        scene: usize,
+        local: impl Fn(usize, &Node) -> Mat4,
+    ) -> Result<Vec<SceneNode<'_>>> {
+        let scene = get(&self.scenes, "scene", scene)?;
+
+        let mut visited = vec![false; self.nodes.len()];
+        let mut nodes: Vec<SceneNode> = Vec::new();
+        // Where each entry of `nodes` has its parent in `nodes`
+        let mut parents: Vec<Option<usize>> = Vec::new();
+        // (node, position of the parent in `nodes`)
+        let mut stack: Vec<(usize, Option<usize>)> =
+            scene.nodes.iter().rev().map(|node| (*node, None)).collect();
+
+        while let Some((index, parent)) = stack.pop() {
+            let node = get(&self.nodes, "node", index)?;
+            if visited[index] {
+                let mut ancestor = parent;
+                while let Some(position) = ancestor {
+                    if nodes[position].index == index {
+                        return Err(Error::Cycle(index));
+                    }
+                    ancestor = parents[position];
+                }
+                return Err(Error::SharedNode(index));
+            }
+            visited[index] = true;
+
+            let local = local(index, node);
+            let (world, depth) = match parent {
+                Some(parent) => (nodes[parent].world * local, nodes[parent].depth + 1),
+                None => (local, 0),
+            };
+            let camera = node
+                .camera
+                .map(|camera| get(&self.cameras, "camera", camera))
+                .transpose()?;
+            let light = node
+                .extensions
+                .lights_punctual
+                .map(|light| get(self.lights(), "light", light.light))
+                .transpose()?;
+            nodes.push(SceneNode {
+                index,
+                node,
+                parent: parent.map(|parent| nodes[parent].index),
+                depth,
+                local,
+                world,
+                camera,
+                light,
+            });
+            parents.push(parent);
+
+            let position = nodes.len() - 1;
+            stack.extend(
+                node.children
+                    .iter()
+                    .rev()
+                    .map(|child| (*child, Some(position))),
+            );
+        }
+
+        Ok(nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{CameraType, LightType};
+
+    fn gltf(nodes: Value, roots: Value) -> Gltf {
+        serde_json::from_value(json!({
+            "asset": {"version": "2.0"},
+            "nodes": nodes,
+            "scenes": [{"nodes": roots}],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn traversal_order_and_transforms() {
+        let gltf = gltf(
+            json!([
+                {"children": [1, 2], "translation": [1, 0, 0]},
+                {"children": [3], "scale": [2, 2, 2]},
+                {"translation": [0, 1, 0]},
+                {"translation": [0, 0, 1]},
+                {},
+            ]),
+            json!([0, 4]),
+        );
+        let nodes = gltf.traverse().unwrap();
+        let order: Vec<_> = nodes
+            .iter()
+            .map(|node| (node.index, node.parent, node.depth))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (0, None, 0),
+                (1, Some(0), 1),
+                (3, Some(1), 2),
+                (2, Some(0), 1),
+                (4, None, 0)
+            ]
+        );
+        assert_eq!(nodes[2].position(), Vec3::new(1.0, 0.0, 2.0));
+        assert_eq!(nodes[3].position(), Vec3::new(1.0, 1.0, 0.0));
+        assert_eq!(nodes[0].forward(), Vec3::NEG_Z);
+
+        let world = gltf.world_matrices().unwrap();
+        assert_eq!(world[3], nodes[2].world);
+    }
+
+    #[test]
+    fn other_scenes() {
+        let mut gltf = gltf(
+            json!([{"translation": [1, 0, 0]}, {"translation": [0, 2, 0]}]),
+            json!([0]),
+        );
+        gltf.scenes
+            .push(serde_json::from_value(json!({"nodes": [1]})).unwrap());
+
+        let nodes = gltf.traverse_scene(1).unwrap();
+        assert_eq!(nodes.len(), 1);
+        assert_eq!(nodes[0].index, 1);
+        assert_eq!(nodes[0].position(), Vec3::new(0.0, 2.0, 0.0));
+
+        // Nodes outside the default scene keep the identity
+        let world = gltf.world_matrices().unwrap();
+        assert_eq!(world[0], Mat4::from_translation(Vec3::X));
+        assert_eq!(world[1], Mat4::IDENTITY);
+
+        gltf.scene = 1;
+        assert_eq!(gltf.traverse().unwrap()[0].index, 1);
+    }
+
+    #[test]
+    fn no_scenes() {
+        let gltf: Gltf = serde_json::from_value(json!({
+            "asset": {"version": "2.0"},
+            "nodes": [{}],
+        }))
+        .unwrap();
+        assert!(gltf.traverse().unwrap().is_empty());
+        assert_eq!(gltf.world_matrices().unwrap(), [Mat4::IDENTITY]);
+    }
+
+    fn lights() -> Gltf {
+        serde_json::from_value(json!({
+            "asset": {"version": "2.0"},
+            "extensionsUsed": ["KHR_lights_punctual"],
+            "extensions": {"KHR_lights_punctual": {"lights": [
+                {"type": "spot", "spot": {}},
+                {"type": "point", "color": [1, 0, 0], "intensity": 5, "range": 10},
+            ]}},
+            "cameras": [{"type": "perspective", "perspective": {"yfov": 1, "znear": 0.1}}],
+            "nodes": [
+                {"children": [1], "translation": [0, 1, 0], "rotation": [0, 0.70710677, 0, 0.70710677]},
+                {"translation": [0, 0, -2], "extensions": {"KHR_lights_punctual": {"light": 0}}},
+                {"camera": 0},
+            ],
+            "scenes": [{"nodes": [0, 2]}],
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn light_defaults() {
+        let gltf = lights();
+        let [spot, point] = gltf.lights() else {
+            panic!("expected two lights");
+        };
+        assert_eq!(spot.ty, LightType::Spot);
+        assert_eq!(spot.color, [1.0; 3]);
+        assert_eq!(spot.intensity, 1.0);
+        assert_eq!(spot.range, None);
+        let cone = spot.spot.unwrap();
+        assert_eq!(cone.inner_cone_angle, 0.0);
+        assert_eq!(cone.outer_cone_angle, std::f32::consts::FRAC_PI_4);
+
+        assert_eq!(point.color, [1.0, 0.0, 0.0]);
+        assert_eq!(point.intensity, 5.0);
+        assert_eq!(point.range, Some(10.0));
+
+        let gltf: Gltf = serde_json::from_value(json!({"asset": {"version": "2.0"}})).unwrap();
+        assert!(gltf.lights().is_empty());
+    }
+
+    #[test]
+    fn lights_and_cameras_on_nodes() {
+        let gltf = lights();
+        let nodes = gltf.traverse().unwrap();
+        assert!(nodes[0].light.is_none());
+
+        // Turned a quarter turn around Y, so -Z becomes -X
+        let light = &nodes[1];
+        assert_eq!(light.light.unwrap().ty, LightType::Spot);
+        assert!(light
+            .position()
+            .abs_diff_eq(Vec3::new(-2.0, 1.0, 0.0), 1e-6));
+        assert!(light.forward().abs_diff_eq(Vec3::NEG_X, 1e-6));
+
+        let camera = &nodes[2];
+        assert!(camera.light.is_none());
+        assert_eq!(camera.camera.unwrap().ty, CameraType::Perspective);
+        assert_eq!(camera.forward(), Vec3::NEG_Z);
+    }
+
+    #[test]
+    fn missing_lights() {
+        let mut gltf = lights();
+        gltf.nodes[1]
+            .extensions
+            .lights_punctual
+            .as_mut()
+            .unwrap()
+            .light = 2;
+        assert!(matches!(
+            gltf.traverse(),
+            Err(Error::Index {
+                kind: "light",
+                index: 2
+            })
+        ));
+        gltf.nodes[2].camera = Some(1);
+        gltf.nodes[1].extensions.lights_punctual = None;
+        assert!(matches!(
+            gltf.traverse(),
+            Err(Error::Index {
+                kind: "camera",
+                index: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn matrices_decompose() {
+        let matrix = Mat4::from_scale_rotation_translation(
+            Vec3::splat(2.0),
+            Quat::from_rotation_y(1.0),
+            Vec3::new(1.0, 2.0, 3.0),
+        );
+        let gltf = gltf(json!([{"matrix": matrix.to_cols_array()}]), json!([0]));
+        let node = &gltf.nodes[0];
+        assert_eq!(node.local_matrix(), matrix);
+        assert!(node.transform().matrix().abs_diff_eq(matrix, 1e-5));
+    }
+
+    #[test]
+    fn cycles() {
+        let gltf = self::gltf(json!([{"children": [0]}]), json!([0]));
+        assert!(matches!(gltf.traverse(), Err(Error::Cycle(0))));
+
+        let gltf = self::gltf(
+            json!([{"children": [1]}, {"children": [2]}, {"children": [1]}]),
+            json!([0]),
+        );
+        assert!(matches!(gltf.traverse(), Err(Error::Cycle(1))));
+        assert!(matches!(gltf.world_matrices(), Err(Error::Cycle(1))));
+    }
+
+    #[test]
+    fn shared_nodes() {
+        // Two parents
+        let gltf = self::gltf(
+            json!([{"children": [2]}, {"children": [2]}, {}]),
+            json!([0, 1]),
+        );
+        assert!(matches!(gltf.traverse(), Err(Error::SharedNode(2))));
+
+        // A root that's also a child
+        let gltf = self::gltf(json!([{"children": [1]}, {}]), json!([0, 1]));
+        assert!(matches!(gltf.traverse(), Err(Error::SharedNode(1))));
+
+        // The same root twice
+        let gltf = self::gltf(json!([{}]), json!([0, 0]));
+        assert!(matches!(gltf.traverse(), Err(Error::SharedNode(0))));
+    }
+
+    #[test]
+    fn missing_nodes() {
+        let gltf = gltf(json!([{"children": [3]}]), json!([0]));
+        assert!(matches!(
+            gltf.traverse(),
+            Err(Error::Index {
+                kind: "node",
+                index: 3
+            })
+        ));
+        assert!(matches!(
+            gltf.traverse_scene(1),
+            Err(Error::Index {
+                kind: "scene",
+                index: 1
+            })
+        ));
+    }
+}
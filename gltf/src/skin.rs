@@ -0,0 +1,167 @@
+use glam::Mat4;
+
+use crate::{get, Buffers, Error, Result};
+
+/// A skin with its inverse bind matrices read out of the buffers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Skeleton {
+    pub joints: Vec<usize>,
+    pub root: Option<usize>,
+    pub inverse_bind_matrices: Vec<Mat4>,
+}
+
+impl Skeleton {
+    pub fn load(glb: &impl Buffers, skin: usize) -> Result<Self> {
+        let skin = get(&glb.gltf().skins, "skin", skin)?;
+        for joint in skin.joints.iter().chain(&skin.skeleton) {
+            get(&glb.gltf().nodes, "node", *joint)?;
+        }
+
+        // Without an accessor every inverse bind matrix is the identity
+        let inverse_bind_matrices = match skin.inverse_bind_matrices {
+            Some(accessor) => {
+                let matrices = get(&glb.gltf().accessors, "accessor", accessor)?.read_mat4(glb)?;
+                if matrices.len() < skin.joints.len() {
+                    return Err(Error::Index {
+                        kind: "inverse bind matrix",
+                        index: matrices.len(),
+                    });
+                }
+                matrices
+            }
+            None => vec![Mat4::IDENTITY; skin.joints.len()],
+        };
+
+        Ok(Self {
+            joints: skin.joints.clone(),
+            root: skin.skeleton,
+            inverse_bind_matrices,
+        })
+    }
+
+    /// The joint matrix palette for skinning, indexed like `JOINTS_n`
+    /// attributes, given world transforms indexed by node. The matrices
+    /// take vertices to world space, so the skinned mesh's own node
+    /// transform shouldn't be applied on top.
+    pub fn joint_matrices(&self, world: &[Mat4]) -> Result<Vec<Mat4>> {
+        self.joints
+            .iter()
+            .zip(&self.inverse_bind_matrices)
+            .map(|(joint, inverse_bind)| Ok(*get(world, "node", *joint)? * *inverse_bind))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Quat, Vec3};
+    use serde_json::{json, Value};
+
+    use super::*;
+    use crate::{fixture, Glb};
+
+    // An arm along y, starting at x = 1 and bending at y = 2
+    fn glb(rotation: Quat, mut skin: Value) -> Glb {
+        fixture::glb(|glb| {
+            let inverse_bind_matrices = glb.push_accessor(
+                &[
+                    Mat4::from_translation(Vec3::new(-1.0, 0.0, 0.0)),
+                    Mat4::from_translation(Vec3::new(-1.0, -2.0, 0.0)),
+                ],
+                None,
+            );
+            if skin.get("inverseBindMatrices").is_some() {
+                skin["inverseBindMatrices"] = json!(inverse_bind_matrices);
+            }
+            json!({
+                "nodes": [
+                    {"children": [1], "translation": [1, 0, 0], "rotation": rotation.to_array()},
+                    {"translation": [0, 2, 0]},
+                    {"skin": 0},
+                ],
+                "scenes": [{"nodes": [0, 2]}],
+                "skins": [skin],
+            })
+        })
+    }
+
+    fn arm(rotation: Quat) -> Glb {
+        glb(
+            rotation,
+            json!({"joints": [0, 1], "skeleton": 0, "inverseBindMatrices": 0}),
+        )
+    }
+
+    #[test]
+    fn bind_pose_is_the_identity() {
+        let glb = arm(Quat::IDENTITY);
+        let skeleton = Skeleton::load(&glb, 0).unwrap();
+        assert_eq!(skeleton.joints, [0, 1]);
+        assert_eq!(skeleton.root, Some(0));
+
+        let world = glb.gltf.world_matrices().unwrap();
+        for matrix in skeleton.joint_matrices(&world).unwrap() {
+            assert!(matrix.abs_diff_eq(Mat4::IDENTITY, 1e-6), "{matrix}");
+        }
+    }
+
+    #[test]
+    fn posed_joints() {
+        let glb = arm(Quat::from_rotation_z(std::f32::consts::FRAC_PI_2));
+        let skeleton = Skeleton::load(&glb, 0).unwrap();
+        let world = glb.gltf.world_matrices().unwrap();
+        let joints = skeleton.joint_matrices(&world).unwrap();
+
+        // The elbow swings round from (1, 2) to (-1, 0), whichever joint
+        // moves it, and the shoulder stays put
+        let elbow = Vec3::new(1.0, 2.0, 0.0);
+        for joint in &joints {
+            let moved = joint.transform_point3(elbow);
+            assert!(
+                moved.abs_diff_eq(Vec3::new(-1.0, 0.0, 0.0), 1e-6),
+                "{moved}"
+            );
+        }
+        let shoulder = joints[0].transform_point3(Vec3::X);
+        assert!(shoulder.abs_diff_eq(Vec3::X, 1e-6), "{shoulder}");
+        // Points past the elbow only bend with the forearm
+        let hand = joints[1].transform_point3(Vec3::new(1.0, 3.0, 0.0));
+        assert!(hand.abs_diff_eq(Vec3::new(-2.0, 0.0, 0.0), 1e-6), "{hand}");
+    }
+
+    #[test]
+    fn missing_inverse_bind_matrices() {
+        let glb = glb(Quat::IDENTITY, json!({"joints": [0, 1]}));
+        let skeleton = Skeleton::load(&glb, 0).unwrap();
+        assert_eq!(skeleton.inverse_bind_matrices, [Mat4::IDENTITY; 2]);
+        let world = glb.gltf.world_matrices().unwrap();
+        assert_eq!(skeleton.joint_matrices(&world).unwrap(), world[..2]);
+
+        // Fewer matrices than joints
+        let glb = self::glb(
+            Quat::IDENTITY,
+            json!({"joints": [0, 1, 2], "inverseBindMatrices": 0}),
+        );
+        assert!(matches!(
+            Skeleton::load(&glb, 0),
+            Err(Error::Index {
+                kind: "inverse bind matrix",
+                index: 2
+            })
+        ));
+    }
+
+    #[test]
+    fn missing_nodes() {
+        let glb = glb(Quat::IDENTITY, json!({"joints": [0, 3]}));
+        assert!(matches!(
+            Skeleton::load(&glb, 0),
+            Err(Error::Index {
+                kind: "node",
+                index: 3
+            })
+        ));
+        let skeleton = Skeleton::load(&arm(Quat::IDENTITY), 0).unwrap();
+        assert!(skeleton.joint_matrices(&[Mat4::IDENTITY]).is_err());
+    }
+}
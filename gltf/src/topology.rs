@@ -0,0 +1,150 @@
+use crate::{Buffers, Error, MeshPrimitive, Mode, Result};
+
+impl Mode {
+    /// Turns indices drawn with this mode into a triangle list, keeping the
+    /// winding of every triangle. Degenerate triangles, which strips use to
+    /// join up, are left out.
+    pub fn triangle_list(self, indices: &[u32]) -> Result<Vec<u32>> {
+        let triangles = indices.len().saturating_sub(2);
+        let triangle = |i: usize| match self {
+            // Every other triangle in a strip is flipped to keep the winding
+            Mode::TriangleStrip if i % 2 == 1 => [indices[i + 1], indices[i], indices[i + 2]],
+            Mode::TriangleStrip => [indices[i], indices[i + 1], indices[i + 2]],
+            _ => [indices[i + 1], indices[i + 2], indices[0]],
+        };
+        match self {
+            Mode::Triangles => Ok(indices[..indices.len() - indices.len() % 3].to_vec()),
+            Mode::TriangleStrip | Mode::TriangleFan => Ok((0..triangles)
+                .map(triangle)
+                .filter(|[a, b, c]| a != b && b != c && c != a)
+                .flatten()
+                .collect()),
+            mode => Err(Error::Mode(mode)),
+        }
+    }
+}
+
+impl MeshPrimitive {
+    /// The number of vertices, going by the positions.
+    pub fn vertex_count(&self, glb: &impl Buffers) -> Result<usize> {
+        Ok(self
+            .attribute(glb, "POSITION")?
+            .map_or(0, |accessor| accessor.count))
+    }
+
+    /// The primitive's triangles as a list of indices, whatever its mode.
+    /// Primitives without indices draw their vertices in order.
+    pub fn triangle_indices(&self, glb: &impl Buffers) -> Result<Vec<u32>> {
+        let indices = match self.get_indices_data(glb)? {
+            Some(indices) => indices,
+            None => (0..self.vertex_count(glb)? as u32).collect(),
+        };
+        self.mode.triangle_list(&indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::Vec3;
+    use serde_json::json;
+
+    use super::*;
+    use crate::fixture;
+
+    // Whether every triangle is wound counter-clockwise when looking down
+    // the Z axis from the `z` side
+    fn facing(positions: &[Vec3], triangles: &[u32], z: f32) -> bool {
+        triangles.chunks_exact(3).all(|triangle| {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+            (b - a).cross(c - a).z * z > 0.0
+        })
+    }
+
+    // Two rows of vertices, zigzagging along X from the top row
+    fn strip(count: usize) -> Vec<Vec3> {
+        (0..count)
+            .map(|i| Vec3::new((i / 2) as f32, (1 - i % 2) as f32, 0.0))
+            .collect()
+    }
+
+    #[test]
+    fn strips() {
+        let triangles = Mode::TriangleStrip.triangle_list(&[0, 1, 2, 3, 4]).unwrap();
+        assert_eq!(triangles, [0, 1, 2, 2, 1, 3, 2, 3, 4]);
+
+        let positions = strip(9);
+        let indices: Vec<u32> = (0..9).collect();
+        let triangles = Mode::TriangleStrip.triangle_list(&indices).unwrap();
+        assert_eq!(triangles.len(), 7 * 3);
+        assert!(facing(&positions, &triangles, 1.0));
+        // Reversing an odd length strip flips the winding of every triangle
+        let indices: Vec<u32> = (0..9).rev().collect();
+        let triangles = Mode::TriangleStrip.triangle_list(&indices).unwrap();
+        assert!(facing(&positions, &triangles, -1.0));
+    }
+
+    #[test]
+    fn joined_strips() {
+        // Two strips joined by repeating the end of one and the start of
+        // the next, which makes four degenerate triangles
+        let mut positions = strip(4);
+        positions.extend(strip(4).iter().map(|p| *p + Vec3::X * 5.0));
+        let indices = [0, 1, 2, 3, 3, 4, 4, 5, 6, 7];
+        let triangles = Mode::TriangleStrip.triangle_list(&indices).unwrap();
+        assert_eq!(triangles, [0, 1, 2, 2, 1, 3, 4, 5, 6, 6, 5, 7]);
+        assert!(facing(&positions, &triangles, 1.0));
+    }
+
+    #[test]
+    fn fans() {
+        let triangles = Mode::TriangleFan.triangle_list(&[0, 1, 2, 3, 4]).unwrap();
+        assert_eq!(triangles, [1, 2, 0, 2, 3, 0, 3, 4, 0]);
+
+        // A hexagon around its centre, counter-clockwise
+        let mut positions = vec![Vec3::ZERO];
+        positions.extend((0..6).map(|i| {
+            let angle = i as f32 * std::f32::consts::TAU / 6.0;
+            Vec3::new(angle.cos(), angle.sin(), 0.0)
+        }));
+        let triangles = Mode::TriangleFan
+            .triangle_list(&[0, 1, 2, 3, 4, 5, 6, 1])
+            .unwrap();
+        assert_eq!(triangles.len(), 6 * 3);
+        assert!(facing(&positions, &triangles, 1.0));
+    }
+
+    #[test]
+    fn short_and_partial_lists() {
+        for mode in [Mode::Triangles, Mode::TriangleStrip, Mode::TriangleFan] {
+            assert!(mode.triangle_list(&[]).unwrap().is_empty());
+            assert!(mode.triangle_list(&[0, 1]).unwrap().is_empty());
+        }
+        let triangles = Mode::Triangles.triangle_list(&[0, 1, 2, 3, 4]).unwrap();
+        assert_eq!(triangles, [0, 1, 2]);
+    }
+
+    #[test]
+    fn points_and_lines_have_no_triangles() {
+        for mode in [Mode::Points, Mode::Lines, Mode::LineLoop, Mode::LineStrip] {
+            assert!(matches!(
+                mode.triangle_list(&[0, 1, 2]),
+                Err(Error::Mode(error)) if error == mode
+            ));
+        }
+    }
+
+    #[test]
+    fn primitives_without_indices() {
+        let glb = fixture::glb(|glb| {
+            json!({"meshes": [{"primitives": [
+                {"attributes": {"POSITION": glb.push_accessor(&strip(5), None)}, "mode": 5},
+            ]}]})
+        });
+        let primitive = &glb.gltf.meshes[0].primitives[0];
+        assert_eq!(primitive.vertex_count(&glb).unwrap(), 5);
+        assert_eq!(
+            primitive.triangle_indices(&glb).unwrap(),
+            [0, 1, 2, 2, 1, 3, 2, 3, 4]
+        );
+    }
+}
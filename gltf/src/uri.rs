@@ -0,0 +1,116 @@
+use std::path::{Component, Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::{Error, Result};
+
+// Data URIs can be megabytes long, which isn't much use in an error message
+fn invalid(uri: &str) -> Error {
+    match uri.char_indices().nth(64) {
+        Some((end, _)) => Error::Uri(format!("{}...", &uri[..end])),
+        None => Error::Uri(uri.to_owned()),
+    }
+}
+
+fn percent_decode(uri: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(uri.len());
+    let mut chars = uri.bytes();
+    while let Some(byte) = chars.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let hex = [chars.next(), chars.next()];
+        let decoded = match hex {
+            [Some(high), Some(low)] => std::str::from_utf8(&[high, low])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        bytes.push(decoded.ok_or_else(|| invalid(uri))?);
+    }
+    Ok(bytes)
+}
+
+/// Loads the contents of a buffer or image URI, either a `data:` URI or a
+/// path relative to `base`. Without a base only data URIs can be loaded.
+pub(crate) fn load(uri: &str, base: Option<&Path>) -> Result<Vec<u8>> {
+    if let Some(data) = uri.strip_prefix("data:") {
+        let (header, data) = data.split_once(',').ok_or_else(|| invalid(uri))?;
+        return if header.ends_with(";base64") {
+            STANDARD.decode(data).map_err(|_| invalid(uri))
+        } else {
+            percent_decode(data)
+        };
+    }
+
+    // Anything with a scheme isn't a relative path
+    if uri
+        .split_once(':')
+        .is_some_and(|(scheme, _)| !scheme.contains('/'))
+    {
+        return Err(invalid(uri));
+    }
+
+    let base = base.ok_or_else(|| invalid(uri))?;
+    let path = String::from_utf8(percent_decode(uri)?).map_err(|_| invalid(uri))?;
+    // Files can't reach outside of the directory they're loaded from
+    let path = Path::new(&path);
+    if path.is_absolute()
+        || path.components().any(|component| {
+            matches!(
+                component,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        })
+    {
+        return Err(invalid(uri));
+    }
+    Ok(std::fs::read(base.join(path))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_uris() {
+        assert_eq!(
+            load("data:application/octet-stream;base64,AQID", None).unwrap(),
+            [1, 2, 3]
+        );
+        assert_eq!(load("data:,a%20b", None).unwrap(), b"a b");
+        assert!(matches!(load("data:a%2", None), Err(Error::Uri(_))));
+    }
+
+    #[test]
+    fn relative_paths() {
+        let base = std::env::temp_dir().join(format!("gltf-uri-{}", std::process::id()));
+        std::fs::create_dir_all(base.join("textures")).unwrap();
+        std::fs::write(base.join("textures/a b.bin"), [4, 5]).unwrap();
+
+        assert_eq!(load("textures/a%20b.bin", Some(&base)).unwrap(), [4, 5]);
+        assert_eq!(load("./textures/a b.bin", Some(&base)).unwrap(), [4, 5]);
+        assert!(matches!(load("textures/a b.bin", None), Err(Error::Uri(_))));
+        assert!(matches!(
+            load("https://example.com/a.bin", Some(&base)),
+            Err(Error::Uri(_))
+        ));
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
+    #[test]
+    fn paths_cant_escape_the_base() {
+        let base = Path::new("models");
+        for uri in [
+            "../secret.bin",
+            "textures/../../secret.bin",
+            "%2E%2E/secret.bin",
+            "/etc/passwd",
+            "%2Fetc/passwd",
+        ] {
+            assert!(matches!(load(uri, Some(base)), Err(Error::Uri(_))), "{uri}");
+        }
+    }
+}
@@ -0,0 +1,1024 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use crate::{Accessor, Buffers, ComponentType, MeshPrimitive, Mode, EXTENSIONS};
+
+/// A problem with an asset, `path` is a JSON pointer to the offending value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    pub path: String,
+    pub message: String,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+// Escapes a key for use in a JSON pointer (RFC 6901)
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+struct Validator<'a, B: Buffers> {
+    glb: &'a B,
+    issues: Vec<Issue>,
+}
+
+impl<B: Buffers> Validator<'_, B> {
+    fn issue(&mut self, path: String, message: String) {
+        self.issues.push(Issue { path, message });
+    }
+
+    fn index(&mut self, path: String, index: usize, length: usize, kind: &str) -> bool {
+        if index >= length {
+            self.issue(path, format!("There is no {kind} {index}"));
+            return false;
+        }
+        true
+    }
+
+    fn references(&mut self) {
+        let gltf = self.glb.gltf();
+        let accessors = gltf.accessors.len();
+        let buffer_views = gltf.buffer_views.len();
+        let nodes = gltf.nodes.len();
+
+        for (i, accessor) in gltf.accessors.iter().enumerate() {
+            if let Some(view) = accessor.buffer_view {
+                self.index(
+                    format!("/accessors/{i}/bufferView"),
+                    view,
+                    buffer_views,
+                    "buffer view",
+                );
+            }
+            if let Some(sparse) = &accessor.sparse {
+                self.index(
+                    format!("/accessors/{i}/sparse/indices/bufferView"),
+                    sparse.indices.buffer_view,
+                    buffer_views,
+                    "buffer view",
+                );
+                self.index(
+                    format!("/accessors/{i}/sparse/values/bufferView"),
+                    sparse.values.buffer_view,
+                    buffer_views,
+                    "buffer view",
+                );
+            }
+        }
+
+        for (i, animation) in gltf.animations.iter().enumerate() {
+            for (j, channel) in animation.channels.iter().enumerate() {
+                let path = format!("/animations/{i}/channels/{j}");
+                self.index(
+                    format!("{path}/sampler"),
+                    channel.sampler,
+                    animation.samplers.len(),
+                    "sampler",
+                );
+                if let Some(node) = channel.target.node {
+                    self.index(format!("{path}/target/node"), node, nodes, "node");
+                }
+            }
+            for (j, sampler) in animation.samplers.iter().enumerate() {
+                let path = format!("/animations/{i}/samplers/{j}");
+                self.index(
+                    format!("{path}/input"),
+                    sampler.input,
+                    accessors,
+                    "accessor",
+                );
+                self.index(
+                    format!("{path}/output"),
+                    sampler.output,
+                    accessors,
+                    "accessor",
+                );
+            }
+        }
+
+        for (i, view) in gltf.buffer_views.iter().enumerate() {
+            self.index(
+                format!("/bufferViews/{i}/buffer"),
+                view.buffer,
+                gltf.buffers.len(),
+                "buffer",
+            );
+        }
+
+        for (i, image) in gltf.images.iter().enumerate() {
+            if let Some(view) = image.buffer_view {
+                self.index(
+                    format!("/images/{i}/bufferView"),
+                    view,
+                    buffer_views,
+                    "buffer view",
+                );
+            }
+        }
+
+        for (i, material) in gltf.materials.iter().enumerate() {
+            let path = format!("/materials/{i}");
+            let textures = [
+                (
+                    "pbrMetallicRoughness/baseColorTexture",
+                    material.pbr.base_color_texture.as_ref().map(|t| t.index),
+                ),
+                (
+                    "pbrMetallicRoughness/metallicRoughnessTexture",
+                    material
+                        .pbr
+                        .metallic_roughness_texture
+                        .as_ref()
+                        .map(|t| t.index),
+                ),
+                (
+                    "normalTexture",
+                    material.normal_texture.as_ref().map(|t| t.index),
+                ),
+                (
+                    "occlusionTexture",
+                    material.occlusion_texture.as_ref().map(|t| t.index),
+                ),
+                (
+                    "emissiveTexture",
+                    material.emissive_texture.as_ref().map(|t| t.index),
+                ),
+            ];
+            for (name, texture) in textures {
+                if let Some(texture) = texture {
+                    self.index(
+                        format!("{path}/{name}/index"),
+                        texture,
+                        gltf.textures.len(),
+                        "texture",
+                    );
+                }
+            }
+        }
+
+        for (i, mesh) in gltf.meshes.iter().enumerate() {
+            for (j, primitive) in mesh.primitives.iter().enumerate() {
+                let path = format!("/meshes/{i}/primitives/{j}");
+                for (name, accessor) in &primitive.attributes {
+                    self.index(
+                        format!("{path}/attributes/{}", escape(name)),
+                        *accessor,
+                        accessors,
+                        "accessor",
+                    );
+                }
+                if let Some(indices) = primitive.indices {
+                    self.index(format!("{path}/indices"), indices, accessors, "accessor");
+                }
+                if let Some(material) = primitive.material {
+                    self.index(
+                        format!("{path}/material"),
+                        material,
+                        gltf.materials.len(),
+                        "material",
+                    );
+                }
+                for (k, target) in primitive.targets.iter().enumerate() {
+                    for (name, accessor) in target {
+                        self.index(
+                            format!("{path}/targets/{k}/{}", escape(name)),
+                            *accessor,
+                            accessors,
+                            "accessor",
+                        );
+                    }
+                }
+            }
+        }
+
+        for (i, node) in gltf.nodes.iter().enumerate() {
+            let path = format!("/nodes/{i}");
+            if let Some(camera) = node.camera {
+                self.index(
+                    format!("{path}/camera"),
+                    camera,
+                    gltf.cameras.len(),
+                    "camera",
+                );
+            }
+            for (j, child) in node.children.iter().enumerate() {
+                self.index(format!("{path}/children/{j}"), *child, nodes, "node");
+            }
+            if let Some(skin) = node.skin {
+                self.index(format!("{path}/skin"), skin, gltf.skins.len(), "skin");
+            }
+            if let Some(mesh) = node.mesh {
+                self.index(format!("{path}/mesh"), mesh, gltf.meshes.len(), "mesh");
+            }
+            if let Some(light) = &node.extensions.lights_punctual {
+                self.index(
+                    format!("{path}/extensions/KHR_lights_punctual/light"),
+                    light.light,
+                    gltf.lights().len(),
+                    "light",
+                );
+            }
+        }
+
+        if !gltf.scenes.is_empty() {
+            self.index("/scene".to_owned(), gltf.scene, gltf.scenes.len(), "scene");
+        }
+        for (i, scene) in gltf.scenes.iter().enumerate() {
+            for (j, node) in scene.nodes.iter().enumerate() {
+                self.index(format!("/scenes/{i}/nodes/{j}"), *node, nodes, "node");
+            }
+        }
+
+        for (i, skin) in gltf.skins.iter().enumerate() {
+            if let Some(matrices) = skin.inverse_bind_matrices {
+                self.index(
+                    format!("/skins/{i}/inverseBindMatrices"),
+                    matrices,
+                    accessors,
+                    "accessor",
+                );
+            }
+            if let Some(skeleton) = skin.skeleton {
+                self.index(format!("/skins/{i}/skeleton"), skeleton, nodes, "node");
+            }
+            for (j, joint) in skin.joints.iter().enumerate() {
+                self.index(format!("/skins/{i}/joints/{j}"), *joint, nodes, "node");
+            }
+        }
+
+        for (i, texture) in gltf.textures.iter().enumerate() {
+            if let Some(sampler) = texture.sampler {
+                self.index(
+                    format!("/textures/{i}/sampler"),
+                    sampler,
+                    gltf.samplers.len(),
+                    "sampler",
+                );
+            }
+            self.index(
+                format!("/textures/{i}/source"),
+                texture.source,
+                gltf.images.len(),
+                "image",
+            );
+        }
+    }
+
+    fn buffer_views(&mut self) {
+        for (i, view) in self.glb.gltf().buffer_views.iter().enumerate() {
+            let Some(buffer) = self.glb.buffer(view.buffer) else {
+                continue;
+            };
+            let end = view.byte_offset.saturating_add(view.byte_length);
+            if end > buffer.len() {
+                self.issue(
+                    format!("/bufferViews/{i}/byteLength"),
+                    format!(
+                        "Bytes {}..{end} are outside of buffer {} of length {}",
+                        view.byte_offset,
+                        view.buffer,
+                        buffer.len()
+                    ),
+                );
+            }
+            if view.byte_stride != 0 && !(4..=252).contains(&view.byte_stride) {
+                self.issue(
+                    format!("/bufferViews/{i}/byteStride"),
+                    format!("Stride {} isn't between 4 and 252", view.byte_stride),
+                );
+            }
+        }
+    }
+
+    // Checks the accessor fits inside its buffer view, returns whether its
+    // data can be read
+    fn accessor_bounds(&mut self, i: usize, accessor: &Accessor) -> bool {
+        let path = format!("/accessors/{i}");
+        let Ok(element_size) = accessor.element_size() else {
+            self.issue(
+                format!("{path}/type"),
+                format!("Invalid accessor type {}", accessor.element_type),
+            );
+            return false;
+        };
+
+        let size = accessor.component_type.size_of();
+        if !accessor.byte_offset.is_multiple_of(size) {
+            self.issue(
+                format!("{path}/byteOffset"),
+                format!("Offset isn't a multiple of the component size {size}"),
+            );
+        }
+
+        let Some(index) = accessor.buffer_view else {
+            return true;
+        };
+        // Buffer views that are missing or too big have already been reported
+        let Ok((view, _)) = self.glb.buffer_view(index) else {
+            return false;
+        };
+        let stride = match view.byte_stride {
+            0 => element_size,
+            stride if stride < element_size => {
+                self.issue(
+                    format!("{path}/bufferView"),
+                    format!(
+                        "Buffer view stride {stride} is less than the element size {element_size}"
+                    ),
+                );
+                return false;
+            }
+            stride => stride,
+        };
+        if accessor.count == 0 {
+            return true;
+        }
+
+        let end = stride
+            .checked_mul(accessor.count - 1)
+            .and_then(|end| end.checked_add(element_size))
+            .and_then(|end| end.checked_add(accessor.byte_offset));
+        match end {
+            Some(end) if end <= view.byte_length => true,
+            _ => {
+                self.issue(
+                    format!("{path}/count"),
+                    format!(
+                        "{} elements at offset {} don't fit in a buffer view of length {}",
+                        accessor.count, accessor.byte_offset, view.byte_length
+                    ),
+                );
+                false
+            }
+        }
+    }
+
+    fn min_max(&mut self, i: usize, accessor: &Accessor, data: &[u8]) {
+        let Ok(components) = accessor.components() else {
+            return;
+        };
+        let bounds = [("min", &accessor.min), ("max", &accessor.max)];
+        let mut valid = true;
+        for (name, bound) in bounds {
+            if let Some(bound) = bound.as_ref().filter(|bound| bound.len() != components) {
+                self.issue(
+                    format!("/accessors/{i}/{name}"),
+                    format!("Expected {components} values but found {}", bound.len()),
+                );
+                valid = false;
+            }
+        }
+        if !valid || accessor.count == 0 {
+            return;
+        }
+
+        // Bounds are stored as raw component values, even when normalized
+        let values: Vec<f64> = match accessor.component_type {
+            ComponentType::I8 => data.iter().map(|x| *x as i8 as f64).collect(),
+            ComponentType::U8 => data.iter().map(|x| *x as f64).collect(),
+            ComponentType::I16 => data
+                .chunks_exact(2)
+                .map(|x| i16::from_le_bytes([x[0], x[1]]) as f64)
+                .collect(),
+            ComponentType::U16 => data
+                .chunks_exact(2)
+                .map(|x| u16::from_le_bytes([x[0], x[1]]) as f64)
+                .collect(),
+            ComponentType::U32 => data
+                .chunks_exact(4)
+                .map(|x| u32::from_le_bytes([x[0], x[1], x[2], x[3]]) as f64)
+                .collect(),
+            ComponentType::F32 => data
+                .chunks_exact(4)
+                .map(|x| f32::from_le_bytes([x[0], x[1], x[2], x[3]]) as f64)
+                .collect(),
+        };
+        let mut min = vec![f64::INFINITY; components];
+        let mut max = vec![f64::NEG_INFINITY; components];
+        for element in values.chunks_exact(components) {
+            for (c, value) in element.iter().enumerate() {
+                min[c] = min[c].min(*value);
+                max[c] = max[c].max(*value);
+            }
+        }
+
+        // Compare at single precision, as writers round floats differently
+        for (name, bound, actual) in [("min", &accessor.min, min), ("max", &accessor.max, max)] {
+            let Some(bound) = bound else {
+                continue;
+            };
+            for (c, (declared, actual)) in bound.iter().zip(actual).enumerate() {
+                if *declared as f32 != actual as f32 {
+                    self.issue(
+                        format!("/accessors/{i}/{name}/{c}"),
+                        format!("Declared {name} is {declared} but the data's {name} is {actual}"),
+                    );
+                }
+            }
+        }
+    }
+
+    // Returns the accessors that could be read
+    fn accessors(&mut self) -> HashSet<usize> {
+        let mut readable = HashSet::new();
+        for (i, accessor) in self.glb.gltf().accessors.iter().enumerate() {
+            let issues = self.issues.len();
+            if !self.accessor_bounds(i, accessor) || self.issues.len() > issues {
+                continue;
+            }
+            match accessor.get_data(self.glb) {
+                Ok(data) => {
+                    self.min_max(i, accessor, &data);
+                    readable.insert(i);
+                }
+                Err(e) => {
+                    let path = match accessor.sparse {
+                        Some(_) => format!("/accessors/{i}/sparse"),
+                        None => format!("/accessors/{i}"),
+                    };
+                    self.issue(path, e.to_string());
+                }
+            }
+        }
+        readable
+    }
+
+    fn attributes(
+        &mut self,
+        path: &str,
+        attributes: &HashMap<String, usize>,
+        target: bool,
+        counts: &mut Vec<(String, usize)>,
+    ) {
+        let mut names: Vec<_> = attributes.keys().collect();
+        names.sort();
+        for name in names {
+            let Some(accessor) = self.glb.gltf().accessors.get(attributes[name]) else {
+                continue;
+            };
+            let path = format!("{path}/{}", escape(name));
+
+            let semantic = name.split('_').next().unwrap_or_default();
+            let expected: &[&str] = match semantic {
+                "POSITION" | "NORMAL" => &["VEC3"],
+                // Only morph targets leave out the handedness
+                "TANGENT" if target => &["VEC3"],
+                "TANGENT" => &["VEC4"],
+                "TEXCOORD" => &["VEC2"],
+                "COLOR" => &["VEC3", "VEC4"],
+                "JOINTS" | "WEIGHTS" => &["VEC4"],
+                _ => &[],
+            };
+            if !expected.is_empty() && !expected.contains(&accessor.element_type.as_str()) {
+                self.issue(
+                    path.clone(),
+                    format!(
+                        "Expected an accessor of type {} but found {}",
+                        expected.join(" or "),
+                        accessor.element_type
+                    ),
+                );
+            }
+            if !self.component_type_allowed(semantic, accessor, target) {
+                let normalized = if accessor.normalized {
+                    "normalized "
+                } else {
+                    ""
+                };
+                self.issue(
+                    path.clone(),
+                    format!(
+                        "Component type {normalized}{:?} isn't allowed for {semantic}",
+                        accessor.component_type
+                    ),
+                );
+            }
+            counts.push((path, accessor.count));
+        }
+    }
+
+    // KHR_mesh_quantization allows integer positions, normals, tangents and
+    // texture coordinates on top of what the core spec allows
+    fn component_type_allowed(&self, semantic: &str, accessor: &Accessor, target: bool) -> bool {
+        use ComponentType::*;
+        let quantized = self
+            .glb
+            .gltf()
+            .extensions_used
+            .iter()
+            .any(|extension| extension == "KHR_mesh_quantization");
+        let (ty, normalized) = (accessor.component_type, accessor.normalized);
+        if ty == F32 {
+            return semantic != "JOINTS";
+        }
+        match semantic {
+            "POSITION" if target => quantized && matches!(ty, I8 | I16),
+            "POSITION" => quantized && ty != U32,
+            "NORMAL" | "TANGENT" => quantized && normalized && matches!(ty, I8 | I16),
+            "TEXCOORD" if target => quantized && matches!(ty, I8 | I16),
+            "TEXCOORD" => (normalized && matches!(ty, U8 | U16)) || (quantized && ty != U32),
+            "COLOR" | "WEIGHTS" => !target && normalized && matches!(ty, U8 | U16),
+            "JOINTS" => !target && !normalized && matches!(ty, U8 | U16),
+            _ => true,
+        }
+    }
+
+    fn primitive(&mut self, path: &str, primitive: &MeshPrimitive, readable: &HashSet<usize>) {
+        let mut counts = Vec::new();
+        self.attributes(
+            &format!("{path}/attributes"),
+            &primitive.attributes,
+            false,
+            &mut counts,
+        );
+        for (k, target) in primitive.targets.iter().enumerate() {
+            self.attributes(&format!("{path}/targets/{k}"), target, true, &mut counts);
+        }
+
+        // Everything is compared against the positions, or whichever
+        // attribute comes first without them
+        let position = format!("{path}/attributes/POSITION");
+        let expected = counts
+            .iter()
+            .find(|(path, _)| *path == position)
+            .or(counts.first())
+            .map(|(_, count)| *count);
+        if let Some(expected) = expected {
+            for (path, count) in &counts {
+                if *count != expected {
+                    self.issue(
+                        path.clone(),
+                        format!(
+                            "Expected {expected} elements like the positions but found {count}"
+                        ),
+                    );
+                }
+            }
+        }
+        let count = counts.iter().map(|(_, count)| *count).min();
+
+        if let Some(position) = primitive.attributes.get("POSITION") {
+            let accessor = self.glb.gltf().accessors.get(*position);
+            if accessor.is_some_and(|accessor| accessor.min.is_none() || accessor.max.is_none()) {
+                self.issue(
+                    format!("{path}/attributes/POSITION"),
+                    "Position accessors need a min and max".to_owned(),
+                );
+            }
+        }
+
+        // Each mode needs enough vertices for at least one of its shapes,
+        // and lists need whole shapes
+        let elements = match primitive.indices {
+            Some(index) => self
+                .glb
+                .gltf()
+                .accessors
+                .get(index)
+                .map(|accessor| accessor.count),
+            None => expected,
+        };
+        if let Some(elements) = elements {
+            let (minimum, multiple) = match primitive.mode {
+                Mode::Points => (1, 1),
+                Mode::Lines => (2, 2),
+                Mode::LineLoop | Mode::LineStrip => (2, 1),
+                Mode::Triangles => (3, 3),
+                Mode::TriangleStrip | Mode::TriangleFan => (3, 1),
+            };
+            if elements < minimum || !elements.is_multiple_of(multiple) {
+                let path = match primitive.indices {
+                    Some(_) => format!("{path}/indices"),
+                    None => format!("{path}/attributes"),
+                };
+                self.issue(
+                    path,
+                    format!(
+                        "{elements} vertices don't make whole {:?} primitives",
+                        primitive.mode
+                    ),
+                );
+            }
+        }
+
+        let Some(index) = primitive.indices else {
+            return;
+        };
+        let Some(accessor) = self.glb.gltf().accessors.get(index) else {
+            return;
+        };
+        let path = format!("{path}/indices");
+        if accessor.element_type != "SCALAR"
+            || !matches!(
+                accessor.component_type,
+                ComponentType::U8 | ComponentType::U16 | ComponentType::U32
+            )
+        {
+            self.issue(
+                path,
+                format!(
+                    "Indices must be unsigned integer scalars, found {} of {:?}",
+                    accessor.element_type, accessor.component_type
+                ),
+            );
+            return;
+        }
+
+        if !readable.contains(&index) {
+            return;
+        }
+        let (Some(count), Ok(indices)) = (count, accessor.read_indices(self.glb)) else {
+            return;
+        };
+        if let Some((i, index)) = indices
+            .iter()
+            .enumerate()
+            .find(|(_, index)| **index as usize >= count)
+        {
+            self.issue(
+                path,
+                format!("Index {i} is {index} but there are only {count} vertices"),
+            );
+        }
+    }
+
+    fn meshes(&mut self, readable: &HashSet<usize>) {
+        for (i, mesh) in self.glb.gltf().meshes.iter().enumerate() {
+            for (j, primitive) in mesh.primitives.iter().enumerate() {
+                self.primitive(&format!("/meshes/{i}/primitives/{j}"), primitive, readable);
+            }
+        }
+    }
+
+    fn skins(&mut self) {
+        for (i, skin) in self.glb.gltf().skins.iter().enumerate() {
+            let Some(accessor) = skin
+                .inverse_bind_matrices
+                .and_then(|accessor| self.glb.gltf().accessors.get(accessor))
+            else {
+                continue;
+            };
+            if accessor.count < skin.joints.len() {
+                self.issue(
+                    format!("/skins/{i}/inverseBindMatrices"),
+                    format!(
+                        "Skin has {} joints but only {} inverse bind matrices",
+                        skin.joints.len(),
+                        accessor.count
+                    ),
+                );
+            }
+        }
+    }
+
+    fn extensions(&mut self) {
+        let gltf = self.glb.gltf();
+        for (i, extension) in gltf.extensions_required.iter().enumerate() {
+            let path = format!("/extensionsRequired/{i}");
+            if !EXTENSIONS.contains(&extension.as_str()) {
+                self.issue(
+                    path.clone(),
+                    format!("Required extension {extension} isn't supported"),
+                );
+            }
+            if !gltf.extensions_used.contains(extension) {
+                self.issue(
+                    path,
+                    format!("Required extension {extension} isn't in extensionsUsed"),
+                );
+            }
+        }
+    }
+}
+
+/// Checks an asset for anything that would stop it from loading or
+/// rendering correctly, an empty list means it's valid.
+pub fn validate(glb: &impl Buffers) -> Vec<Issue> {
+    let mut validator = Validator {
+        glb,
+        issues: Vec::new(),
+    };
+    validator.extensions();
+    validator.references();
+    validator.buffer_views();
+    let readable = validator.accessors();
+    validator.meshes(&readable);
+    validator.skins();
+    validator.issues
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Mat4, Vec2, Vec3};
+    use serde_json::json;
+
+    use super::*;
+    use crate::{fixture, BufferView, Glb};
+
+    // An indexed triangle in a scene, which is valid
+    fn triangle() -> Glb {
+        fixture::glb(|glb| {
+            let positions = [Vec3::ZERO, Vec3::X, Vec3::Y];
+            json!({
+                "meshes": [{"primitives": [{
+                    "attributes": {
+                        "POSITION": glb.push_accessor(&positions, Some(BufferView::ARRAY_BUFFER)),
+                    },
+                    "indices": glb.push_indices(&[0, 1, 2]),
+                }]}],
+                "nodes": [{"mesh": 0}],
+                "scenes": [{"nodes": [0]}],
+            })
+        })
+    }
+
+    fn paths(glb: &Glb) -> Vec<String> {
+        validate(glb).into_iter().map(|issue| issue.path).collect()
+    }
+
+    fn messages(glb: &Glb) -> Vec<String> {
+        validate(glb)
+            .into_iter()
+            .map(|issue| issue.to_string())
+            .collect()
+    }
+
+    fn attribute(glb: &mut Glb, name: &str, accessor: usize) {
+        glb.gltf.meshes[0].primitives[0]
+            .attributes
+            .insert(name.to_owned(), accessor);
+    }
+
+    #[test]
+    fn valid() {
+        assert_eq!(validate(&triangle()), []);
+    }
+
+    #[test]
+    fn missing_references() {
+        let mut glb = triangle();
+        glb.gltf.meshes[0].primitives[0].indices = Some(9);
+        attribute(&mut glb, "_A/B~", 7);
+        glb.gltf.nodes =
+            serde_json::from_value(json!([{"mesh": 0, "children": [5], "skin": 0}])).unwrap();
+        glb.gltf.scene = 3;
+        glb.gltf.materials = serde_json::from_value(json!([{
+            "pbrMetallicRoughness": {"baseColorTexture": {"index": 4}},
+        }]))
+        .unwrap();
+        glb.gltf.textures = serde_json::from_value(json!([{"source": 2}])).unwrap();
+
+        assert_eq!(
+            messages(&glb),
+            [
+                "/materials/0/pbrMetallicRoughness/baseColorTexture/index: There is no texture 4",
+                "/meshes/0/primitives/0/attributes/_A~1B~0: There is no accessor 7",
+                "/meshes/0/primitives/0/indices: There is no accessor 9",
+                "/nodes/0/children/0: There is no node 5",
+                "/nodes/0/skin: There is no skin 0",
+                "/scene: There is no scene 3",
+                "/textures/0/source: There is no image 2",
+            ]
+        );
+    }
+
+    #[test]
+    fn buffer_views() {
+        let mut glb = triangle();
+        glb.gltf.buffer_views[0].byte_length = 1000;
+        glb.gltf.buffer_views[1].byte_stride = 1;
+        glb.gltf.buffer_views.push(BufferView {
+            buffer: 1,
+            byte_offset: 0,
+            byte_length: 4,
+            byte_stride: 0,
+            target: None,
+            extensions: Default::default(),
+        });
+        assert_eq!(
+            paths(&glb),
+            [
+                "/bufferViews/2/buffer",
+                "/bufferViews/0/byteLength",
+                "/bufferViews/1/byteStride",
+                // Accessors in views that don't fit aren't checked any
+                // further, but the indices can be read
+                "/accessors/1/bufferView",
+            ]
+        );
+    }
+
+    #[test]
+    fn accessor_layout() {
+        let mut glb = triangle();
+        glb.gltf.accessors[0].element_type = "VEC5".to_owned();
+        assert_eq!(
+            paths(&glb),
+            [
+                "/accessors/0/type",
+                "/meshes/0/primitives/0/attributes/POSITION"
+            ]
+        );
+
+        let mut glb = triangle();
+        glb.gltf.accessors[0].byte_offset = 2;
+        assert_eq!(
+            paths(&glb),
+            ["/accessors/0/byteOffset", "/accessors/0/count"]
+        );
+
+        let mut glb = triangle();
+        glb.gltf.buffer_views[0].byte_stride = 8;
+        assert_eq!(paths(&glb), ["/accessors/0/bufferView"]);
+
+        let mut glb = triangle();
+        glb.gltf.accessors[1].count = 6;
+        assert_eq!(
+            messages(&glb),
+            ["/accessors/1/count: 6 elements at offset 0 don't fit in a buffer view of length 6"]
+        );
+    }
+
+    #[test]
+    fn bounds() {
+        let mut glb = triangle();
+        glb.gltf.accessors[0].min = Some(vec![0.0, 0.0]);
+        assert_eq!(paths(&glb), ["/accessors/0/min"]);
+
+        let mut glb = triangle();
+        glb.gltf.accessors[0].max = Some(vec![1.0, 2.0, 0.0]);
+        assert_eq!(
+            messages(&glb),
+            ["/accessors/0/max/1: Declared max is 2 but the data's max is 1"]
+        );
+
+        let mut glb = triangle();
+        glb.gltf.accessors[0].min = None;
+        assert_eq!(
+            messages(&glb),
+            ["/meshes/0/primitives/0/attributes/POSITION: Position accessors need a min and max"]
+        );
+    }
+
+    #[test]
+    fn unreadable_sparse() {
+        let mut glb = triangle();
+        glb.gltf.accessors[0].sparse = serde_json::from_value(json!({
+            "count": 1,
+            "indices": {"bufferView": 1, "componentType": 5123},
+            "values": {"bufferView": 0},
+        }))
+        .unwrap();
+        // The first sparse index is 0, which is past the end of an empty
+        // accessor
+        glb.gltf.accessors[0].count = 0;
+        glb.gltf.accessors[0].min = None;
+        glb.gltf.accessors[0].max = None;
+        glb.gltf.meshes.clear();
+        glb.gltf.nodes.clear();
+        glb.gltf.scenes.clear();
+        assert_eq!(
+            messages(&glb),
+            ["/accessors/0/sparse: There is no element 0"]
+        );
+    }
+
+    #[test]
+    fn attribute_types() {
+        let mut glb = triangle();
+        let normals = glb.push_accessor(&[Vec2::ZERO; 3], None);
+        attribute(&mut glb, "NORMAL", normals);
+        let joints = glb.push_accessor(&[[0.0f32; 4]; 3], None);
+        attribute(&mut glb, "JOINTS_0", joints);
+        let tangents = glb.push_accessor(&[Vec3::X; 3], None);
+        attribute(&mut glb, "TANGENT", tangents);
+        let uvs = glb.push_accessor(&[[0u8, 0, 0, 0]; 3], None);
+        glb.gltf.accessors[uvs].element_type = "VEC2".to_owned();
+        glb.gltf.accessors[uvs].count = 6;
+        glb.gltf.accessors[uvs].min = None;
+        glb.gltf.accessors[uvs].max = None;
+        attribute(&mut glb, "TEXCOORD_0", uvs);
+
+        assert_eq!(
+            messages(&glb),
+            [
+                "/meshes/0/primitives/0/attributes/JOINTS_0: Component type F32 isn't allowed for JOINTS",
+                "/meshes/0/primitives/0/attributes/NORMAL: Expected an accessor of type VEC3 but found VEC2",
+                "/meshes/0/primitives/0/attributes/TANGENT: Expected an accessor of type VEC4 but found VEC3",
+                "/meshes/0/primitives/0/attributes/TEXCOORD_0: Component type U8 isn't allowed for TEXCOORD",
+                "/meshes/0/primitives/0/attributes/TEXCOORD_0: Expected 3 elements like the positions but found 6",
+            ]
+        );
+
+        // Normalized, or with quantization, byte texture coordinates are fine
+        glb.gltf.accessors[uvs].count = 3;
+        glb.gltf.accessors[uvs].normalized = true;
+        glb.gltf.meshes[0].primitives[0]
+            .attributes
+            .remove("JOINTS_0");
+        glb.gltf.meshes[0].primitives[0].attributes.remove("NORMAL");
+        glb.gltf.meshes[0].primitives[0]
+            .attributes
+            .remove("TANGENT");
+        // Morph targets move tangents without touching their handedness
+        glb.gltf.meshes[0].primitives[0].targets =
+            vec![HashMap::from([("TANGENT".to_owned(), tangents)])];
+        assert_eq!(validate(&glb), []);
+        glb.gltf.accessors[uvs].normalized = false;
+        glb.gltf.extensions_used = vec!["KHR_mesh_quantization".to_owned()];
+        assert_eq!(validate(&glb), []);
+    }
+
+    #[test]
+    fn quantized_positions() {
+        let mut glb = triangle();
+        let positions = glb.push_accessor(&[[0u16, 0, 0, 0], [1, 0, 0, 0], [0, 1, 0, 0]], None);
+        glb.gltf.accessors[positions].element_type = "VEC3".to_owned();
+        glb.gltf.accessors[positions].component_type = ComponentType::I16;
+        glb.gltf.accessors[positions].count = 4;
+        glb.gltf.accessors[positions].min = Some(vec![0.0; 3]);
+        glb.gltf.accessors[positions].max = Some(vec![1.0, 1.0, 0.0]);
+        attribute(&mut glb, "POSITION", positions);
+        assert_eq!(
+            messages(&glb),
+            ["/meshes/0/primitives/0/attributes/POSITION: Component type I16 isn't allowed for POSITION"]
+        );
+        glb.gltf.extensions_used = vec!["KHR_mesh_quantization".to_owned()];
+        assert_eq!(validate(&glb), []);
+    }
+
+    #[test]
+    fn whole_primitives() {
+        let mut glb = triangle();
+        let indices = glb.push_indices(&[0, 1, 2, 0]);
+        glb.gltf.meshes[0].primitives[0].indices = Some(indices);
+        assert_eq!(
+            messages(&glb),
+            ["/meshes/0/primitives/0/indices: 4 vertices don't make whole Triangles primitives"]
+        );
+        glb.gltf.meshes[0].primitives[0].mode = Mode::TriangleStrip;
+        assert_eq!(validate(&glb), []);
+
+        let mut glb = triangle();
+        glb.gltf.meshes[0].primitives[0].indices = None;
+        glb.gltf.meshes[0].primitives[0].mode = Mode::Lines;
+        assert_eq!(paths(&glb), ["/meshes/0/primitives/0/attributes"]);
+    }
+
+    #[test]
+    fn indices() {
+        let mut glb = triangle();
+        let indices = glb.push_accessor(&[0.0f32, 1.0, 2.0], None);
+        glb.gltf.meshes[0].primitives[0].indices = Some(indices);
+        assert_eq!(
+            messages(&glb),
+            ["/meshes/0/primitives/0/indices: Indices must be unsigned integer scalars, found SCALAR of F32"]
+        );
+
+        let mut glb = triangle();
+        let indices = glb.push_indices(&[0, 1, 5]);
+        glb.gltf.meshes[0].primitives[0].indices = Some(indices);
+        assert_eq!(
+            messages(&glb),
+            ["/meshes/0/primitives/0/indices: Index 2 is 5 but there are only 3 vertices"]
+        );
+    }
+
+    #[test]
+    fn skins() {
+        let mut glb = triangle();
+        let matrices = glb.push_accessor(&[Mat4::IDENTITY], None);
+        glb.gltf.skins = serde_json::from_value(json!([{
+            "joints": [0, 0],
+            "inverseBindMatrices": matrices,
+        }]))
+        .unwrap();
+        assert_eq!(
+            messages(&glb),
+            ["/skins/0/inverseBindMatrices: Skin has 2 joints but only 1 inverse bind matrices"]
+        );
+    }
+
+    #[test]
+    fn extensions() {
+        let mut glb = triangle();
+        glb.gltf.extensions_required = vec![
+            "KHR_materials_unknown".to_owned(),
+            "KHR_mesh_quantization".to_owned(),
+        ];
+        glb.gltf.extensions_used = vec!["KHR_materials_unknown".to_owned()];
+        assert_eq!(
+            messages(&glb),
+            [
+                "/extensionsRequired/0: Required extension KHR_materials_unknown isn't supported",
+                "/extensionsRequired/1: Required extension KHR_mesh_quantization isn't in extensionsUsed",
+            ]
+        );
+    }
+}
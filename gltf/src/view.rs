@@ -0,0 +1,337 @@
+use std::marker::PhantomData;
+
+use crate::{slice, Accessor, Buffers, ComponentType, Element, Error, Result};
+
+// Sparse values replacing some of the elements. The indices are strictly
+// increasing, so lookups can binary search them in place
+struct Sparse<'a> {
+    indices: &'a [u8],
+    component_type: ComponentType,
+    values: &'a [u8],
+}
+
+impl Sparse<'_> {
+    fn index(&self, i: usize) -> usize {
+        match self.component_type {
+            ComponentType::U8 => self.indices[i] as usize,
+            ComponentType::U16 => {
+                u16::from_le_bytes([self.indices[i * 2], self.indices[i * 2 + 1]]) as usize
+            }
+            _ => {
+                let bytes = &self.indices[i * 4..i * 4 + 4];
+                u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize
+            }
+        }
+    }
+
+    fn find(&self, index: usize) -> Option<usize> {
+        let (mut low, mut high) = (0, self.indices.len() / self.component_type.size_of());
+        while low < high {
+            let middle = (low + high) / 2;
+            match self.index(middle).cmp(&index) {
+                std::cmp::Ordering::Less => low = middle + 1,
+                std::cmp::Ordering::Greater => high = middle,
+                std::cmp::Ordering::Equal => return Some(middle),
+            }
+        }
+        None
+    }
+}
+
+/// An accessor's elements read in place from the buffer, without copying
+/// or decoding anything up front.
+pub struct View<'a, T> {
+    // `None` for accessors without a buffer view, which are all zeros
+    data: Option<&'a [u8]>,
+    stride: usize,
+    count: usize,
+    sparse: Option<Sparse<'a>>,
+    element: PhantomData<T>,
+}
+
+impl<'a, T: Element> View<'a, T> {
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn get(&self, index: usize) -> Option<T> {
+        if index >= self.count {
+            return None;
+        }
+        let size = std::mem::size_of::<T>();
+        if let Some(sparse) = &self.sparse {
+            if let Some(i) = sparse.find(index) {
+                let value = &sparse.values[i * size..(i + 1) * size];
+                return Some(bytemuck::pod_read_unaligned(value));
+            }
+        }
+        Some(match self.data {
+            Some(data) => {
+                bytemuck::pod_read_unaligned(&data[index * self.stride..index * self.stride + size])
+            }
+            None => T::zeroed(),
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = T> + '_ {
+        (0..self.count).filter_map(|index| self.get(index))
+    }
+
+    /// The elements as a slice, if they're tightly packed, suitably aligned
+    /// and not sparse, which is the usual case for vertex data.
+    pub fn as_slice(&self) -> Option<&'a [T]> {
+        if self.sparse.is_some() || self.stride != std::mem::size_of::<T>() {
+            return None;
+        }
+        let data = self.data?;
+        bytemuck::try_cast_slice(&data[..self.count * self.stride]).ok()
+    }
+}
+
+impl Accessor {
+    /// A typed view of the accessor over the buffer. `T` has to match the
+    /// accessor's type and component type exactly, normalized integers
+    /// aren't converted to floats.
+    pub fn view<'a, T: Element>(&self, glb: &'a impl Buffers) -> Result<View<'a, T>> {
+        if self.component_type != T::COMPONENT_TYPE {
+            return Err(Error::ComponentType(self.component_type));
+        }
+        if self.element_type != T::TYPE {
+            return Err(Error::UnexpectedType {
+                expected: const { &[T::TYPE] },
+                found: self.element_type.clone(),
+            });
+        }
+
+        // None of the element types have any matrix padding, so elements
+        // are exactly the size of `T`
+        let size = std::mem::size_of::<T>();
+        let mut stride = size;
+        let data = match self.buffer_view {
+            Some(buffer_view) => {
+                let (buffer_view, view) = glb.buffer_view(buffer_view)?;
+                if buffer_view.byte_stride != 0 {
+                    stride = buffer_view.byte_stride;
+                }
+                let length = match self.count {
+                    0 => 0,
+                    count => stride
+                        .checked_mul(count - 1)
+                        .and_then(|length| length.checked_add(size))
+                        .ok_or(Error::Bounds {
+                            start: self.byte_offset,
+                            end: usize::MAX,
+                            length: view.len(),
+                        })?,
+                };
+                Some(slice(view, self.byte_offset, length)?)
+            }
+            None => None,
+        };
+
+        let sparse = match &self.sparse {
+            Some(sparse) => {
+                let component_type = sparse.indices.component_type;
+                if !matches!(
+                    component_type,
+                    ComponentType::U8 | ComponentType::U16 | ComponentType::U32
+                ) {
+                    return Err(Error::ComponentType(component_type));
+                }
+                let (_, view) = glb.buffer_view(sparse.indices.buffer_view)?;
+                let indices = slice(
+                    view,
+                    sparse.indices.byte_offset,
+                    sparse.count.saturating_mul(component_type.size_of()),
+                )?;
+                let (_, view) = glb.buffer_view(sparse.values.buffer_view)?;
+                let values = slice(
+                    view,
+                    sparse.values.byte_offset,
+                    sparse.count.saturating_mul(size),
+                )?;
+                Some(Sparse {
+                    indices,
+                    component_type,
+                    values,
+                })
+            }
+            None => None,
+        };
+
+        Ok(View {
+            data,
+            stride,
+            count: self.count,
+            sparse,
+            element: PhantomData,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::{Vec2, Vec3};
+    use serde_json::json;
+
+    use super::*;
+    use crate::fixture;
+
+    fn bytes<T: bytemuck::Pod>(values: &[T]) -> Vec<u8> {
+        bytemuck::cast_slice(values).to_vec()
+    }
+
+    #[test]
+    fn strided() {
+        // Each Vec2 is followed by a float that isn't part of it
+        let glb = fixture::raw(
+            json!([{"buffer": 0, "byteLength": 36, "byteStride": 12}]),
+            json!([{"bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC2"}]),
+            bytes(&[1.0f32, 2.0, -1.0, 3.0, 4.0, -1.0, 5.0, 6.0, -1.0]),
+        );
+        let view = glb.gltf.accessors[0].view::<Vec2>(&glb).unwrap();
+        assert_eq!(view.len(), 3);
+        assert_eq!(view.get(1), Some(Vec2::new(3.0, 4.0)));
+        assert_eq!(view.get(3), None);
+        assert_eq!(
+            view.iter().collect::<Vec<_>>(),
+            [
+                Vec2::new(1.0, 2.0),
+                Vec2::new(3.0, 4.0),
+                Vec2::new(5.0, 6.0)
+            ]
+        );
+        assert!(view.as_slice().is_none());
+    }
+
+    #[test]
+    fn misaligned_slices() {
+        // The same floats at offsets 0 and 1 into the buffer
+        let mut bin = bytes(&[1.0f32, 2.0]);
+        bin.push(0);
+        bin.extend_from_slice(&bytes(&[1.0f32, 2.0]));
+        let glb = fixture::raw(
+            json!([
+                {"buffer": 0, "byteLength": 8},
+                {"buffer": 0, "byteOffset": 9, "byteLength": 8},
+            ]),
+            json!([
+                {"bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR"},
+                {"bufferView": 1, "componentType": 5126, "count": 2, "type": "SCALAR"},
+            ]),
+            bin,
+        );
+        let start = glb.buffers[0].as_ptr() as usize;
+        for (accessor, offset) in [(0, 0), (1, 9)] {
+            let view = glb.gltf.accessors[accessor].view::<f32>(&glb).unwrap();
+            assert_eq!(view.iter().collect::<Vec<_>>(), [1.0, 2.0]);
+            // Misaligned data can still be read element by element, but
+            // not borrowed as a slice
+            let aligned = (start + offset).is_multiple_of(4);
+            assert_eq!(view.as_slice(), aligned.then_some(&[1.0, 2.0][..]));
+        }
+    }
+
+    #[test]
+    fn sparse_search() {
+        let indices = bytes(&[1u32, 5, 9, 200]);
+        let sparse = Sparse {
+            indices: &indices,
+            component_type: ComponentType::U32,
+            values: &[],
+        };
+        for (index, expected) in [(1, Some(0)), (5, Some(1)), (9, Some(2)), (200, Some(3))] {
+            assert_eq!(sparse.find(index), expected);
+        }
+        for index in [0, 2, 8, 10, 199, 201] {
+            assert_eq!(sparse.find(index), None);
+        }
+
+        let indices = [3u8];
+        let sparse = Sparse {
+            indices: &indices,
+            component_type: ComponentType::U8,
+            values: &[],
+        };
+        assert_eq!(sparse.find(3), Some(0));
+        assert_eq!(sparse.find(2), None);
+    }
+
+    #[test]
+    fn sparse_with_a_buffer_view() {
+        let mut bin = bytes(&[10u16, 20, 30, 40, 50]);
+        bin.extend_from_slice(&[0, 2, 4, 0]);
+        bin.extend_from_slice(&bytes(&[1u16, 3, 5]));
+        let glb = fixture::raw(
+            json!([
+                {"buffer": 0, "byteLength": 10},
+                {"buffer": 0, "byteOffset": 10, "byteLength": 3},
+                {"buffer": 0, "byteOffset": 14, "byteLength": 6},
+            ]),
+            json!([{"bufferView": 0, "componentType": 5123, "count": 5, "type": "SCALAR", "sparse": {
+                "count": 3,
+                "indices": {"bufferView": 1, "componentType": 5121},
+                "values": {"bufferView": 2},
+            }}]),
+            bin,
+        );
+        let view = glb.gltf.accessors[0].view::<u16>(&glb).unwrap();
+        assert_eq!(view.iter().collect::<Vec<_>>(), [1, 20, 3, 40, 5]);
+        assert!(view.as_slice().is_none());
+    }
+
+    #[test]
+    fn sparse_without_a_buffer_view() {
+        let mut bin = bytes(&[2u16, 0]);
+        bin.extend_from_slice(&bytes(&[7.0f32, 8.0, 9.0]));
+        let glb = fixture::raw(
+            json!([
+                {"buffer": 0, "byteLength": 4},
+                {"buffer": 0, "byteOffset": 4, "byteLength": 12},
+            ]),
+            json!([
+                {"componentType": 5126, "count": 3, "type": "VEC3", "sparse": {
+                    "count": 1,
+                    "indices": {"bufferView": 0, "componentType": 5123},
+                    "values": {"bufferView": 1},
+                }},
+                {"componentType": 5126, "count": 2, "type": "VEC3"},
+            ]),
+            bin,
+        );
+        let view = glb.gltf.accessors[0].view::<Vec3>(&glb).unwrap();
+        assert_eq!(
+            view.iter().collect::<Vec<_>>(),
+            [Vec3::ZERO, Vec3::ZERO, Vec3::new(7.0, 8.0, 9.0)]
+        );
+        assert!(view.as_slice().is_none());
+
+        // Without sparse values either, every element is zero
+        let view = glb.gltf.accessors[1].view::<Vec3>(&glb).unwrap();
+        assert_eq!(view.iter().collect::<Vec<_>>(), [Vec3::ZERO; 2]);
+        assert!(view.as_slice().is_none());
+    }
+
+    #[test]
+    fn wrong_element_type() {
+        let glb = fixture::raw(
+            json!([{"buffer": 0, "byteLength": 8}]),
+            json!([{"bufferView": 0, "componentType": 5126, "count": 1, "type": "VEC2"}]),
+            bytes(&[1.0f32, 2.0]),
+        );
+        let accessor = &glb.gltf.accessors[0];
+        assert!(matches!(
+            accessor.view::<u32>(&glb),
+            Err(Error::ComponentType(ComponentType::F32))
+        ));
+        assert!(matches!(
+            accessor.view::<Vec3>(&glb),
+            Err(Error::UnexpectedType { expected: ["VEC3"], found }) if found == "VEC2"
+        ));
+        assert!(accessor.view::<Vec2>(&glb).is_ok());
+    }
+}
@@ -0,0 +1,405 @@
+use std::io::Write;
+
+use bytemuck::Pod;
+use glam::{Mat4, Quat, Vec2, Vec3, Vec4};
+
+use crate::{get, Accessor, Buffer, BufferView, ComponentType, Error, Glb, Gltf, Result};
+
+/// A type that can be stored in an accessor, such as a vertex attribute.
+pub trait Element: Pod {
+    type Component: Pod + Into<f64>;
+    const COMPONENT_TYPE: ComponentType;
+    const TYPE: &'static str;
+}
+
+macro_rules! impl_element {
+    ($($ty:ty => $component:ty, $component_type:ident, $element_type:literal),* $(,)?) => {
+        $(
+            impl Element for $ty {
+                type Component = $component;
+                const COMPONENT_TYPE: ComponentType = ComponentType::$component_type;
+                const TYPE: &'static str = $element_type;
+            }
+        )*
+    };
+}
+
+impl_element! {
+    f32 => f32, F32, "SCALAR",
+    [f32; 2] => f32, F32, "VEC2",
+    [f32; 3] => f32, F32, "VEC3",
+    [f32; 4] => f32, F32, "VEC4",
+    [f32; 16] => f32, F32, "MAT4",
+    Vec2 => f32, F32, "VEC2",
+    Vec3 => f32, F32, "VEC3",
+    Vec4 => f32, F32, "VEC4",
+    Quat => f32, F32, "VEC4",
+    Mat4 => f32, F32, "MAT4",
+    u8 => u8, U8, "SCALAR",
+    u16 => u16, U16, "SCALAR",
+    u32 => u32, U32, "SCALAR",
+    [u8; 4] => u8, U8, "VEC4",
+    [u16; 4] => u16, U16, "VEC4",
+}
+
+impl BufferView {
+    pub const ARRAY_BUFFER: u32 = 34962;
+    pub const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+}
+
+impl Gltf {
+    pub fn to_json(&self) -> Result<String> {
+        let mut json = serde_json::to_value(self)?;
+        // `scene` always has a value, but can't point at a scene that isn't there
+        if self.scenes.is_empty() {
+            if let Some(json) = json.as_object_mut() {
+                json.remove("scene");
+            }
+        }
+        Ok(serde_json::to_string(&json)?)
+    }
+}
+
+fn pad(bytes: &mut Vec<u8>, with: u8) {
+    bytes.resize(bytes.len().next_multiple_of(4), with);
+}
+
+impl Glb {
+    /// An empty asset to build up and write out.
+    pub fn new() -> Self {
+        Self {
+            gltf: Gltf::default(),
+            buffers: Vec::new(),
+            images: Vec::new(),
+        }
+    }
+
+    /// Appends `data` to the first buffer in a new buffer view, returning
+    /// the index of an accessor over it. Pass the accessor to
+    /// `MeshPrimitive::attributes` for vertex data.
+    pub fn push_accessor<T: Element>(&mut self, data: &[T], target: Option<u32>) -> usize {
+        self.push(data, target, false)
+    }
+
+    /// Like `push_accessor`, but integers are read back as fractions of their
+    /// largest value, as packed colours and weights need.
+    pub fn push_normalized_accessor<T: Element>(
+        &mut self,
+        data: &[T],
+        target: Option<u32>,
+    ) -> usize {
+        debug_assert_ne!(T::COMPONENT_TYPE, ComponentType::F32);
+        self.push(data, target, true)
+    }
+
+    fn push<T: Element>(&mut self, data: &[T], target: Option<u32>, normalized: bool) -> usize {
+        // Only the first buffer can go in the binary chunk, so one is put in
+        // front of any that live in other files, like those of a `.gltf`
+        if !matches!(self.gltf.buffers.first(), Some(Buffer { uri: None, .. })) {
+            self.buffers.insert(0, Vec::new());
+            self.gltf.buffers.insert(
+                0,
+                Buffer {
+                    uri: None,
+                    byte_length: 0,
+                    extensions: Default::default(),
+                },
+            );
+            for buffer_view in &mut self.gltf.buffer_views {
+                buffer_view.buffer += 1;
+                if let Some(compression) = &mut buffer_view.extensions.meshopt_compression {
+                    compression.buffer += 1;
+                }
+            }
+        }
+
+        // Buffer views start at a multiple of 4 so any component type is
+        // aligned, and the buffer is kept padded to match
+        let buffer = &mut self.buffers[0];
+        pad(buffer, 0);
+        let byte_offset = buffer.len();
+        buffer.extend_from_slice(bytemuck::cast_slice(data));
+        self.gltf.buffers[0].byte_length = buffer.len();
+
+        self.gltf.buffer_views.push(BufferView {
+            buffer: 0,
+            byte_offset,
+            byte_length: std::mem::size_of_val(data),
+            byte_stride: 0,
+            target,
+            extensions: Default::default(),
+        });
+
+        let components: &[T::Component] = bytemuck::cast_slice(data);
+        let size = components.len() / data.len().max(1);
+        let (mut min, mut max) = (vec![f64::INFINITY; size], vec![f64::NEG_INFINITY; size]);
+        for element in components.chunks_exact(size.max(1)) {
+            for (i, component) in element.iter().enumerate() {
+                let component: f64 = (*component).into();
+                min[i] = min[i].min(component);
+                max[i] = max[i].max(component);
+            }
+        }
+
+        // JSON has no infinities, and a component that's all NaN never moves
+        // off them, so bounds are left out unless they can be written
+        let bounded = !data.is_empty() && min.iter().chain(&max).all(|x| x.is_finite());
+
+        self.gltf.accessors.push(Accessor {
+            buffer_view: Some(self.gltf.buffer_views.len() - 1),
+            byte_offset: 0,
+            component_type: T::COMPONENT_TYPE,
+            normalized,
+            count: data.len(),
+            element_type: T::TYPE.to_owned(),
+            min: bounded.then_some(min),
+            max: bounded.then_some(max),
+            sparse: None,
+        });
+        self.gltf.accessors.len() - 1
+    }
+
+    /// Appends triangle indices, as 16 bit indices if they all fit.
+    pub fn push_indices(&mut self, indices: &[u32]) -> usize {
+        let target = Some(BufferView::ELEMENT_ARRAY_BUFFER);
+        if indices.iter().all(|index| *index <= u16::MAX as u32) {
+            let indices: Vec<u16> = indices.iter().map(|index| *index as u16).collect();
+            self.push_accessor(&indices, target)
+        } else {
+            self.push_accessor(indices, target)
+        }
+    }
+
+    /// Writes a binary `.glb`, with the first buffer as the binary chunk if
+    /// it doesn't have a URI. Any other buffers need URIs.
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<()> {
+        let mut bin = None;
+        for (i, buffer) in self.gltf.buffers.iter().enumerate() {
+            match (&buffer.uri, i) {
+                (Some(_), _) => (),
+                (None, 0) => bin = Some(get(&self.buffers, "buffer", 0)?.as_slice()),
+                (None, _) => {
+                    return Err(Error::Index {
+                        kind: "URI for buffer",
+                        index: i,
+                    })
+                }
+            }
+        }
+
+        let mut json = self.gltf.to_json()?.into_bytes();
+        pad(&mut json, b' ');
+        // Padded as it's written, rather than copying the whole buffer
+        let bin = bin.map(|bin| (bin, bin.len().next_multiple_of(4)));
+
+        // Lengths are stored as u32s, so GLBs can't reach 4 GiB
+        let size = |size: usize| {
+            u32::try_from(size).map_err(|_| Error::TooLarge {
+                size,
+                max: u32::MAX as usize,
+            })
+        };
+        let length = 12 + 8 + json.len() + bin.map_or(0, |(_, padded_len)| 8 + padded_len);
+        writer.write_all(&Self::MAGIC.to_le_bytes())?;
+        writer.write_all(&2u32.to_le_bytes())?;
+        writer.write_all(&size(length)?.to_le_bytes())?;
+
+        writer.write_all(&size(json.len())?.to_le_bytes())?;
+        writer.write_all(&Self::JSON.to_le_bytes())?;
+        writer.write_all(&json)?;
+
+        if let Some((bin, padded_len)) = bin {
+            writer.write_all(&size(padded_len)?.to_le_bytes())?;
+            writer.write_all(&Self::BIN.to_le_bytes())?;
+            writer.write_all(bin)?;
+            writer.write_all(&[0; 3][..padded_len - bin.len()])?;
+        }
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl Default for Glb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{Buffers, GlbRef, Material};
+
+    fn round_trip(glb: &Glb) -> Glb {
+        Glb::load(&glb.to_bytes().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn accessors_round_trip() {
+        let positions = [Vec3::new(1.0, 2.0, 3.0), Vec3::new(-4.0, 5.0, -6.0)];
+        let uvs = [Vec2::new(0.0, 1.0), Vec2::new(0.5, 0.25)];
+        let rotations = [Quat::from_rotation_x(1.0), Quat::IDENTITY];
+        let matrices = [Mat4::from_translation(Vec3::X), Mat4::IDENTITY];
+        let weights = [0.25f32, 0.75];
+        let joints = [[0u16, 1, 2, 300], [4, 5, 6, 7]];
+        let colours = [[255u8, 0, 128, 255], [1, 2, 3, 4]];
+
+        let mut glb = Glb::new();
+        let accessors = [
+            glb.push_accessor(&positions, Some(BufferView::ARRAY_BUFFER)),
+            glb.push_accessor(&uvs, Some(BufferView::ARRAY_BUFFER)),
+            glb.push_accessor(&rotations, None),
+            glb.push_accessor(&matrices, None),
+            glb.push_accessor(&weights, None),
+            glb.push_accessor(&joints, Some(BufferView::ARRAY_BUFFER)),
+            // Three bytes, so the next buffer view needs padding
+            glb.push_accessor(&[7u8, 8, 9], None),
+            glb.push_accessor(&colours, Some(BufferView::ARRAY_BUFFER)),
+        ];
+
+        for glb in [&round_trip(&glb), &glb] {
+            let accessor = |i: usize| &glb.gltf.accessors[accessors[i]];
+            assert_eq!(accessor(0).read_vec3_f32(glb).unwrap(), positions);
+            assert_eq!(accessor(1).read_vec2(glb).unwrap(), uvs);
+            assert_eq!(
+                accessor(2).read_vec4(glb).unwrap(),
+                rotations.map(Vec4::from)
+            );
+            assert_eq!(accessor(3).read_mat4(glb).unwrap(), matrices);
+            assert_eq!(accessor(4).read_scalar(glb).unwrap(), weights);
+            assert_eq!(accessor(5).read_joints(glb).unwrap(), joints);
+            assert_eq!(accessor(6).read_indices(glb).unwrap(), [7, 8, 9]);
+            assert_eq!(
+                accessor(7).read_floats(glb).unwrap(),
+                [255.0, 0.0, 128.0, 255.0, 1.0, 2.0, 3.0, 4.0]
+            );
+        }
+
+        let glb = round_trip(&glb);
+        for buffer_view in &glb.gltf.buffer_views {
+            assert_eq!(buffer_view.byte_offset % 4, 0);
+        }
+        let accessor = &glb.gltf.accessors[accessors[0]];
+        assert_eq!(accessor.min, Some(vec![-4.0, 2.0, -6.0]));
+        assert_eq!(accessor.max, Some(vec![1.0, 5.0, 3.0]));
+        assert_eq!(glb.gltf.buffer_views[accessors[7]].target, Some(34962));
+    }
+
+    #[test]
+    fn normalized_colours_validate() {
+        let mut glb = Glb::new();
+        let positions = glb.push_accessor(&[Vec3::ZERO, Vec3::X, Vec3::Y], None);
+        let colours = [[255u8, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 51]];
+        let colours = glb.push_normalized_accessor(&colours, Some(BufferView::ARRAY_BUFFER));
+        glb.gltf.meshes = vec![serde_json::from_value(json!({"primitives": [{
+            "attributes": {"POSITION": positions, "COLOR_0": colours},
+        }]}))
+        .unwrap()];
+
+        let glb = round_trip(&glb);
+        assert!(crate::validate(&glb).is_empty());
+        let colours = glb.gltf.accessors[colours].read_vec4(&glb).unwrap();
+        assert_eq!(colours[0], Vec4::new(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(colours[2].w, 0.2);
+    }
+
+    #[test]
+    fn non_finite_bounds() {
+        let mut glb = Glb::new();
+        let nan = glb.push_accessor(&[Vec2::new(f32::NAN, 1.0); 2], None);
+        let infinite = glb.push_accessor(&[f32::INFINITY, 0.0], None);
+        let glb = round_trip(&glb);
+        for accessor in [nan, infinite] {
+            let accessor = &glb.gltf.accessors[accessor];
+            assert_eq!((&accessor.min, &accessor.max), (&None, &None));
+        }
+        let values = glb.gltf.accessors[nan].read_vec2(&glb).unwrap();
+        assert!(values[0].x.is_nan());
+        assert_eq!(values[1].y, 1.0);
+    }
+
+    #[test]
+    fn indices_round_trip() {
+        let small = [0, 1, 2, 2, 1, 65535];
+        let large = [0, 1, 65536];
+        let mut glb = Glb::new();
+        let accessors = [glb.push_indices(&small), glb.push_indices(&large)];
+
+        let bytes = glb.to_bytes().unwrap();
+        let glb = GlbRef::load(&bytes).unwrap();
+        let accessor = |i: usize| &glb.gltf().accessors[accessors[i]];
+        assert_eq!(accessor(0).component_type, ComponentType::U16);
+        assert_eq!(accessor(0).read_indices(&glb).unwrap(), small);
+        assert_eq!(accessor(1).component_type, ComponentType::U32);
+        assert_eq!(accessor(1).read_indices(&glb).unwrap(), large);
+        assert_eq!(
+            glb.gltf.buffer_views[0].target,
+            Some(BufferView::ELEMENT_ARRAY_BUFFER)
+        );
+    }
+
+    #[test]
+    fn json_round_trips() {
+        let mut glb = Glb::new();
+        glb.gltf.materials = vec![serde_json::from_value(json!({
+            "name": "glow",
+            "emissiveFactor": [1.0, 0.5, 0.0],
+        }))
+        .unwrap()];
+
+        let bytes = glb.to_bytes().unwrap();
+        assert_eq!(bytes.len() % 4, 0);
+        let glb = round_trip(&glb);
+        let material: &Material = &glb.gltf.materials[0];
+        assert_eq!(material.emissive_factor, Some([1.0, 0.5, 0.0]));
+        assert!(glb.gltf.to_json().unwrap().contains("\"emissiveFactor\""));
+        // Without any buffers there's no binary chunk
+        assert!(glb.buffers.is_empty());
+    }
+
+    #[test]
+    fn pushing_past_external_buffers() {
+        // 1.0 and 2.0 as little endian floats
+        let json = json!({
+            "asset": {"version": "2.0"},
+            "buffers": [{"uri": "data:application/octet-stream;base64,AACAPwAAAEA=", "byteLength": 8}],
+            "bufferViews": [{"buffer": 0, "byteLength": 8}],
+            "accessors": [{"bufferView": 0, "componentType": 5126, "count": 2, "type": "SCALAR"}],
+        });
+        let mut glb = Glb::load_gltf(json.to_string().as_bytes(), "").unwrap();
+        let pushed = glb.push_accessor(&[3.0f32], None);
+        assert_eq!(glb.gltf.buffers[0].uri, None);
+        assert_eq!(glb.gltf.buffer_views[0].buffer, 1);
+
+        let glb = round_trip(&glb);
+        assert_eq!(glb.gltf.buffers.len(), 2);
+        assert_eq!(glb.gltf.accessors[0].read_scalar(&glb).unwrap(), [1.0, 2.0]);
+        assert_eq!(glb.gltf.accessors[pushed].read_scalar(&glb).unwrap(), [3.0]);
+    }
+
+    #[test]
+    fn external_buffers_need_uris() {
+        let mut glb = Glb::new();
+        glb.push_accessor(&[1.0f32], None);
+        glb.buffers.push(vec![0; 4]);
+        glb.gltf.buffers.push(Buffer {
+            uri: None,
+            byte_length: 4,
+            extensions: Default::default(),
+        });
+        assert!(matches!(
+            glb.to_bytes(),
+            Err(Error::Index {
+                kind: "URI for buffer",
+                index: 1
+            })
+        ));
+    }
+}
@@ -0,0 +1,139 @@
+use glam::{IVec2, IVec3, IVec4, Mat2, Mat3, Mat4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+pub const fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+pub const fn max(a: usize, b: usize) -> usize {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+/// A type with a GLSL std140 layout, for uniform buffers. Derive it with
+/// `#[derive(Std140)]` from thanatos_macros.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no std140 layout",
+    note = "std140 fields must be scalars, glam vectors or matrices, arrays, or other `Std140` structs"
+)]
+pub trait Std140 {
+    const ALIGN: usize;
+    const SIZE: usize;
+
+    /// Writes `self` into `bytes`, which is exactly `SIZE` long.
+    fn write_std140(&self, bytes: &mut [u8]);
+
+    fn as_std140(&self) -> Vec<u8> {
+        let mut bytes = vec![0; Self::SIZE];
+        self.write_std140(&mut bytes);
+        bytes
+    }
+}
+
+/// A type with a GLSL std430 layout, for storage buffers. Derive it with
+/// `#[derive(Std430)]` from thanatos_macros.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no std430 layout",
+    note = "std430 fields must be scalars, glam vectors or matrices, arrays, or other `Std430` structs"
+)]
+pub trait Std430 {
+    const ALIGN: usize;
+    const SIZE: usize;
+
+    /// Writes `self` into `bytes`, which is exactly `SIZE` long.
+    fn write_std430(&self, bytes: &mut [u8]);
+
+    fn as_std430(&self) -> Vec<u8> {
+        let mut bytes = vec![0; Self::SIZE];
+        self.write_std430(&mut bytes);
+        bytes
+    }
+}
+
+macro_rules! impl_layouts {
+    ($($tt:tt)*) => {
+        impl_layout!(Std140, write_std140, 16; $($tt)*);
+        impl_layout!(Std430, write_std430, 1; $($tt)*);
+    };
+}
+
+// Arrays and matrix columns are padded out to `$min_align` in std140. That's
+// all this macro covers: std140 also rounds struct alignment up to 16, which
+// the derive in thanatos_macros handles.
+macro_rules! impl_layout {
+    ($trait:ident, $write:ident, $min_align:expr; scalars: $($scalar:ty),*; vectors: $($vector:ty => $component:ty, $n:literal),*; matrices: $($matrix:ty => $column:ty, $columns:literal),*) => {
+        $(
+            impl $trait for $scalar {
+                const ALIGN: usize = std::mem::size_of::<$scalar>();
+                const SIZE: usize = std::mem::size_of::<$scalar>();
+
+                fn $write(&self, bytes: &mut [u8]) {
+                    bytes.copy_from_slice(&self.to_ne_bytes())
+                }
+            }
+        )*
+
+        $(
+            impl $trait for $vector {
+                // vec3 is aligned like a vec4 but only takes up 3 components
+                const ALIGN: usize = <$component as $trait>::ALIGN * if $n == 3 { 4 } else { $n };
+                const SIZE: usize = <$component as $trait>::SIZE * $n;
+
+                fn $write(&self, bytes: &mut [u8]) {
+                    let size = <$component as $trait>::SIZE;
+                    self.to_array()
+                        .iter()
+                        .zip(bytes.chunks_exact_mut(size))
+                        .for_each(|(component, bytes)| component.$write(bytes))
+                }
+            }
+        )*
+
+        $(
+            impl $trait for $matrix {
+                const ALIGN: usize = max(<$column as $trait>::ALIGN, $min_align);
+                const SIZE: usize = align_to(<$column as $trait>::SIZE, <Self as $trait>::ALIGN) * $columns;
+
+                fn $write(&self, bytes: &mut [u8]) {
+                    let stride = align_to(<$column as $trait>::SIZE, <Self as $trait>::ALIGN);
+                    (0..$columns).for_each(|i| {
+                        self.col(i).$write(
+                            &mut bytes[i * stride..i * stride + <$column as $trait>::SIZE],
+                        )
+                    })
+                }
+            }
+        )*
+
+        impl $trait for bool {
+            const ALIGN: usize = 4;
+            const SIZE: usize = 4;
+
+            fn $write(&self, bytes: &mut [u8]) {
+                (*self as u32).$write(bytes)
+            }
+        }
+
+        impl<T: $trait, const N: usize> $trait for [T; N] {
+            const ALIGN: usize = max(<T as $trait>::ALIGN, $min_align);
+            const SIZE: usize = align_to(<T as $trait>::SIZE, <Self as $trait>::ALIGN) * N;
+
+            fn $write(&self, bytes: &mut [u8]) {
+                let stride = align_to(<T as $trait>::SIZE, <Self as $trait>::ALIGN);
+                self.iter()
+                    .enumerate()
+                    .for_each(|(i, item)| item.$write(&mut bytes[i * stride..i * stride + <T as $trait>::SIZE]))
+            }
+        }
+    };
+}
+
+impl_layouts! {
+    scalars: f32, f64, i32, u32;
+    vectors: Vec2 => f32, 2, Vec3 => f32, 3, Vec4 => f32, 4,
+        IVec2 => i32, 2, IVec3 => i32, 3, IVec4 => i32, 4,
+        UVec2 => u32, 2, UVec3 => u32, 3, UVec4 => u32, 4;
+    matrices: Mat2 => Vec2, 2, Mat3 => Vec3, 3, Mat4 => Vec4, 4
+}
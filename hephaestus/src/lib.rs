@@ -2,6 +2,7 @@ pub mod buffer;
 pub mod command;
 pub mod descriptor;
 pub mod image;
+pub mod layout;
 pub mod pipeline;
 pub mod task;
 pub mod vertex;
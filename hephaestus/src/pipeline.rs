@@ -300,11 +300,10 @@ impl<'a> GraphicsBuilder<'a> {
         let attributes = vertex_info
             .attributes
             .into_iter()
-            .enumerate()
-            .map(|(i, (ty, offset))| {
+            .map(|(location, ty, offset)| {
                 VertexInputAttributeDescription::builder()
                     .binding(0)
-                    .location(i as u32)
+                    .location(location)
                     .format(ty.to_format())
                     .offset(offset as u32)
                     .build()
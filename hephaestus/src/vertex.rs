@@ -1,24 +1,172 @@
 use ash::vk::Format;
+use bytemuck::{Pod, Zeroable};
+use glam::{IVec2, IVec3, IVec4, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Scalar {
+    U8,
+    I8,
+    U16,
+    I16,
+    U32,
+    I32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum AttributeType {
+    Float,
     Vec2,
     Vec3,
     Vec4,
+    Integer(Scalar, u8),
+    Normalized(Scalar, u8),
 }
 
 impl AttributeType {
     pub fn to_format(self) -> Format {
         match self {
+            Self::Float => Format::R32_SFLOAT,
             Self::Vec2 => Format::R32G32_SFLOAT,
             Self::Vec3 => Format::R32G32B32_SFLOAT,
             Self::Vec4 => Format::R32G32B32A32_SFLOAT,
+            Self::Integer(scalar, components) => match (scalar, components) {
+                (Scalar::U8, 1) => Format::R8_UINT,
+                (Scalar::U8, 2) => Format::R8G8_UINT,
+                (Scalar::U8, 3) => Format::R8G8B8_UINT,
+                (Scalar::U8, 4) => Format::R8G8B8A8_UINT,
+                (Scalar::I8, 1) => Format::R8_SINT,
+                (Scalar::I8, 2) => Format::R8G8_SINT,
+                (Scalar::I8, 3) => Format::R8G8B8_SINT,
+                (Scalar::I8, 4) => Format::R8G8B8A8_SINT,
+                (Scalar::U16, 1) => Format::R16_UINT,
+                (Scalar::U16, 2) => Format::R16G16_UINT,
+                (Scalar::U16, 3) => Format::R16G16B16_UINT,
+                (Scalar::U16, 4) => Format::R16G16B16A16_UINT,
+                (Scalar::I16, 1) => Format::R16_SINT,
+                (Scalar::I16, 2) => Format::R16G16_SINT,
+                (Scalar::I16, 3) => Format::R16G16B16_SINT,
+                (Scalar::I16, 4) => Format::R16G16B16A16_SINT,
+                (Scalar::U32, 1) => Format::R32_UINT,
+                (Scalar::U32, 2) => Format::R32G32_UINT,
+                (Scalar::U32, 3) => Format::R32G32B32_UINT,
+                (Scalar::U32, 4) => Format::R32G32B32A32_UINT,
+                (Scalar::I32, 1) => Format::R32_SINT,
+                (Scalar::I32, 2) => Format::R32G32_SINT,
+                (Scalar::I32, 3) => Format::R32G32B32_SINT,
+                (Scalar::I32, 4) => Format::R32G32B32A32_SINT,
+                _ => panic!("Unsupported vertex attribute {self:?}"),
+            },
+            Self::Normalized(scalar, components) => match (scalar, components) {
+                (Scalar::U8, 1) => Format::R8_UNORM,
+                (Scalar::U8, 2) => Format::R8G8_UNORM,
+                (Scalar::U8, 3) => Format::R8G8B8_UNORM,
+                (Scalar::U8, 4) => Format::R8G8B8A8_UNORM,
+                (Scalar::I8, 1) => Format::R8_SNORM,
+                (Scalar::I8, 2) => Format::R8G8_SNORM,
+                (Scalar::I8, 3) => Format::R8G8B8_SNORM,
+                (Scalar::I8, 4) => Format::R8G8B8A8_SNORM,
+                (Scalar::U16, 1) => Format::R16_UNORM,
+                (Scalar::U16, 2) => Format::R16G16_UNORM,
+                (Scalar::U16, 3) => Format::R16G16B16_UNORM,
+                (Scalar::U16, 4) => Format::R16G16B16A16_UNORM,
+                (Scalar::I16, 1) => Format::R16_SNORM,
+                (Scalar::I16, 2) => Format::R16G16_SNORM,
+                (Scalar::I16, 3) => Format::R16G16B16_SNORM,
+                (Scalar::I16, 4) => Format::R16G16B16A16_SNORM,
+                _ => panic!("Unsupported vertex attribute {self:?}"),
+            },
         }
     }
 }
 
+/// Integer components the shader reads as floats in `[0, 1]` (or `[-1, 1]`
+/// when signed), e.g. `Normalized<[u8; 4]>` for an 8 bit colour.
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Normalized<T>(pub T);
+
+unsafe impl<T: Zeroable> Zeroable for Normalized<T> {}
+unsafe impl<T: Pod> Pod for Normalized<T> {}
+
+/// A type that can be a field of a vertex.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` can't be used as a vertex attribute",
+    note = "use f32, a glam vector, or an array of up to 4 integers (wrapped in `Normalized` to read them as floats)"
+)]
+pub trait Attribute {
+    const TYPE: AttributeType;
+}
+
+macro_rules! impl_attribute {
+    ($($ty:ty => $attribute:expr),* $(,)?) => {
+        $(impl Attribute for $ty {
+            const TYPE: AttributeType = $attribute;
+        })*
+    };
+}
+
+impl_attribute! {
+    f32 => AttributeType::Float,
+    [f32; 1] => AttributeType::Float,
+    [f32; 2] => AttributeType::Vec2,
+    [f32; 3] => AttributeType::Vec3,
+    [f32; 4] => AttributeType::Vec4,
+    Vec2 => AttributeType::Vec2,
+    Vec3 => AttributeType::Vec3,
+    Vec4 => AttributeType::Vec4,
+    u32 => AttributeType::Integer(Scalar::U32, 1),
+    i32 => AttributeType::Integer(Scalar::I32, 1),
+    UVec2 => AttributeType::Integer(Scalar::U32, 2),
+    UVec3 => AttributeType::Integer(Scalar::U32, 3),
+    UVec4 => AttributeType::Integer(Scalar::U32, 4),
+    IVec2 => AttributeType::Integer(Scalar::I32, 2),
+    IVec3 => AttributeType::Integer(Scalar::I32, 3),
+    IVec4 => AttributeType::Integer(Scalar::I32, 4),
+}
+
+macro_rules! impl_integer_attribute {
+    ($($ty:ty => $scalar:ident),*) => {
+        $(
+            impl<const N: usize> Attribute for [$ty; N] {
+                const TYPE: AttributeType = {
+                    assert!(N >= 1 && N <= 4, "vertex attributes have 1 to 4 components");
+                    AttributeType::Integer(Scalar::$scalar, N as u8)
+                };
+            }
+        )*
+    };
+}
+
+impl_integer_attribute!(u8 => U8, i8 => I8, u16 => U16, i16 => I16, u32 => U32, i32 => I32);
+
+macro_rules! impl_normalized_attribute {
+    ($($ty:ty => $scalar:ident),*) => {
+        $(
+            impl Attribute for Normalized<$ty> {
+                const TYPE: AttributeType = AttributeType::Normalized(Scalar::$scalar, 1);
+            }
+
+            impl<const N: usize> Attribute for Normalized<[$ty; N]> {
+                const TYPE: AttributeType = {
+                    assert!(N >= 1 && N <= 4, "vertex attributes have 1 to 4 components");
+                    AttributeType::Normalized(Scalar::$scalar, N as u8)
+                };
+            }
+        )*
+    };
+}
+
+impl_normalized_attribute!(u8 => U8, i8 => I8, u16 => U16, i16 => I16);
+
+/// A vertex type that knows its own layout, see `#[derive(VertexLayout)]`
+/// in thanatos_macros.
+pub trait Layout {
+    fn info() -> Info;
+}
+
 pub struct Info {
     pub stride: usize,
-    pub attributes: Vec<(AttributeType, usize)>,
+    pub attributes: Vec<(u32, AttributeType, usize)>,
 }
 
 impl Info {
@@ -29,8 +177,18 @@ impl Info {
         }
     }
 
-    pub fn attribute(mut self, ty: AttributeType, offset: usize) -> Self {
-        self.attributes.push((ty, offset));
+    /// Adds an attribute at the location after the previous one.
+    pub fn attribute(self, ty: AttributeType, offset: usize) -> Self {
+        let location = self
+            .attributes
+            .last()
+            .map(|(location, _, _)| location + 1)
+            .unwrap_or(0);
+        self.attribute_at(location, ty, offset)
+    }
+
+    pub fn attribute_at(mut self, location: u32, ty: AttributeType, offset: usize) -> Self {
+        self.attributes.push((location, ty, offset));
         self
     }
 }
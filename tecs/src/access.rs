@@ -0,0 +1,42 @@
+use std::any::TypeId;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Borrows {
+    pub reads: Vec<TypeId>,
+    pub writes: Vec<TypeId>,
+}
+
+impl Borrows {
+    pub fn conflicts(&self, other: &Borrows) -> bool {
+        self.writes
+            .iter()
+            .any(|ty| other.reads.contains(ty) || other.writes.contains(ty))
+            || other.writes.iter().any(|ty| self.reads.contains(ty))
+    }
+}
+
+/// The resources and components a system touches, so systems that don't
+/// conflict can be scheduled alongside each other. Systems that can't say
+/// what they touch are `exclusive` and conflict with everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Access {
+    pub exclusive: bool,
+    pub resources: Borrows,
+    pub components: Borrows,
+}
+
+impl Access {
+    pub fn exclusive() -> Self {
+        Self {
+            exclusive: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn conflicts(&self, other: &Access) -> bool {
+        self.exclusive
+            || other.exclusive
+            || self.resources.conflicts(&other.resources)
+            || self.components.conflicts(&other.components)
+    }
+}
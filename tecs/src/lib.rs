@@ -1,6 +1,12 @@
 #![feature(impl_trait_in_assoc_type)]
 
+mod access;
+mod prefab;
+mod tasks;
 mod vecany;
+pub use access::{Access, Borrows};
+pub use prefab::{PrefabError, Prefabs};
+pub use tasks::Tasks;
 pub use vecany::VecAny;
 
 use std::{
@@ -17,6 +23,18 @@ use std::{
 pub trait System<E> {
     fn event(&self, world: &mut World<E>, event: &E);
     fn tick(&self, world: &mut World<E>);
+
+    fn access(&self) -> Access {
+        Access::exclusive()
+    }
+}
+
+pub trait EventType {
+    type Event;
+}
+
+impl<E> EventType for World<E> {
+    type Event = E;
 }
 
 struct Handler<T>(T);
@@ -37,68 +55,13 @@ impl<E, T: Fn(&mut World<E>)> System<E> for Ticker<T> {
 }
 
 pub trait Archetype: Any {
+    type Ref<'a>;
+    type Mut<'a>;
+
     fn columns() -> Vec<TypeId>;
     fn add(self, table: &mut Table);
-}
-
-#[macro_export]
-macro_rules! impl_archetype {
-    (struct $for:ident { $( $field:ident: $type:ty ),* $(,)?}) => {
-        /*
-        concat_idents::concat_idents!(for_ref = $for, Ref {
-            pub struct for_ref<'a> {
-                $($field: &'a $type,)*
-            }
-        });
-
-        concat_idents::concat_idents!(for_mut = $for, Mut {
-            pub struct for_mut<'a> {
-                $($field: &'a mut $type,)*
-            }
-        });
-*/
-
-        impl tecs::Archetype for $for {
-            /*
-            concat_idents::concat_idents!(for_ref = $for, Ref {
-                type Ref<'a> = for_ref<'a>;
-
-                fn from_components<'a>(components: &'a std::collections::HashMap<std::any::TypeId, std::cell::RefCell<tecs::VecAny>>, indices: &[u32]) -> for_ref<'a> {
-                    let mut indices = indices.iter();
-
-                    for_ref {
-                        $($field: std::cell::Ref::map(components.get(&std::any::TypeId::of::<$type>()).unwrap().borrow(), |x| x.downcast_ref::<$type>().unwrap().get(*indices.next().unwrap() as usize).unwrap()),)*
-                    }
-                }
-            });
-            concat_idents::concat_idents!(for_mut = $for, Mut {
-                type Mut<'a> = for_mut<'a>;
-
-                fn from_components_mut<'a>(components: &'a std::collections::HashMap<std::any::TypeId, std::cell::RefCell<tecs::VecAny>>, indices: &[u32]) -> for_mut<'a> {
-                    let mut indices = indices.iter();
-
-                    for_mut {
-                        $($field: std::cell::RefMut::map(components.get(&std::any::TypeId::of::<$type>()).unwrap().borrow_mut(), |x| x.downcast_mut::<$type>().unwrap().get_mut(*indices.next().unwrap() as usize).unwrap()),)*
-                    }
-                }
-            });
-            */
-
-            fn columns() -> Vec<std::any::TypeId> {
-                vec![$(std::any::TypeId::of::<$type>()),*]
-            }
-
-            fn add(self, table: &mut tecs::Table) {
-                table.length += 1;
-                let mut columns = table.columns_mut();
-                $(
-                    columns.next().unwrap().push::<$type>(self.$field);
-                )*
-            }
-
-
-        }
-    };
+    fn iter(table: &Table) -> impl Iterator<Item = Self::Ref<'_>>;
+    fn iter_mut(table: &Table) -> impl Iterator<Item = Self::Mut<'_>>;
 }
 
 pub struct RowIndex(u32);
@@ -147,10 +110,11 @@ impl Table {
     }
 
     pub fn has_column<T: 'static>(&self) -> bool {
-        self.columns
-            .iter()
-            .find(|(ty, _)| *ty == TypeId::of::<T>())
-            .is_some()
+        self.has_column_id(TypeId::of::<T>())
+    }
+
+    fn has_column_id(&self, ty: TypeId) -> bool {
+        self.columns.iter().any(|(column, _)| *column == ty)
     }
 
     pub fn column<T: 'static>(&self) -> Option<Ref<'_, [T]>> {
@@ -174,11 +138,45 @@ impl Table {
             })
     }
 
+    pub fn rows<T: 'static>(&self) -> Option<Rows<'_, T>> {
+        self.column().map(|column| Rows(Some(column)))
+    }
+
+    pub fn rows_mut<T: 'static>(&self) -> Option<RowsMut<'_, T>> {
+        self.column_mut().map(|column| RowsMut(Some(column)))
+    }
+
     pub fn len(&self) -> usize {
         self.length
     }
 }
 
+pub struct Rows<'a, T>(Option<Ref<'a, [T]>>);
+
+impl<'a, T> Iterator for Rows<'a, T> {
+    type Item = Ref<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let column = self.0.take().filter(|column| !column.is_empty())?;
+        let (row, rest) = Ref::map_split(column, |column| column.split_first().unwrap());
+        self.0 = Some(rest);
+        Some(row)
+    }
+}
+
+pub struct RowsMut<'a, T>(Option<RefMut<'a, [T]>>);
+
+impl<'a, T> Iterator for RowsMut<'a, T> {
+    type Item = RefMut<'a, T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let column = self.0.take().filter(|column| !column.is_empty())?;
+        let (row, rest) = RefMut::map_split(column, |column| column.split_first_mut().unwrap());
+        self.0 = Some(rest);
+        Some(row)
+    }
+}
+
 pub struct Columns<'a, T> {
     columns: Vec<Ref<'a, [T]>>,
 }
@@ -224,14 +222,14 @@ impl<'a, T> ColumnsMut<'a, T> {
 pub trait Query<E> {
     type Output<'a>;
 
-    fn filter(table: &(&TypeId, &Table)) -> bool;
+    fn filter(table: &(&ArchetypeId, &Table)) -> bool;
     fn data<'a>(tables: &[&'a Table]) -> Self::Output<'a>;
 }
 
 impl<T: 'static, E> Query<E> for &'_ T {
     type Output<'a> = Columns<'a, T>;
 
-    fn filter(table: &(&TypeId, &Table)) -> bool {
+    fn filter(table: &(&ArchetypeId, &Table)) -> bool {
         table.1.has_column::<T>()
     }
 
@@ -243,7 +241,7 @@ impl<T: 'static, E> Query<E> for &'_ T {
 impl<T: 'static, E> Query<E> for &'_ mut T {
     type Output<'a> = ColumnsMut<'a, T>;
 
-    fn filter(table: &(&TypeId, &Table)) -> bool {
+    fn filter(table: &(&ArchetypeId, &Table)) -> bool {
         table.1.has_column::<T>()
     }
 
@@ -260,7 +258,7 @@ macro_rules! impl_query {
         impl<Event, $($ty: Query<Event>),+> Query<Event> for ($($ty),+,) {
             type Output<'a> = ($($ty::Output<'a>),+,);
 
-            fn filter(table: &(&TypeId, &Table)) -> bool {
+            fn filter(table: &(&ArchetypeId, &Table)) -> bool {
                 $($ty::filter(table))&&+
             }
 
@@ -284,7 +282,7 @@ pub struct With<T>(PhantomData<T>);
 impl<E, T: 'static> Query<E> for With<T> {
     type Output<'a> = ();
 
-    fn filter(table: &(&TypeId, &Table)) -> bool {
+    fn filter(table: &(&ArchetypeId, &Table)) -> bool {
         table.1.has_column::<T>()
     }
 
@@ -297,7 +295,7 @@ pub struct Without<T>(PhantomData<T>);
 impl<E, T: 'static> Query<E> for Without<T> {
     type Output<'a> = ();
 
-    fn filter(table: &(&TypeId, &Table)) -> bool {
+    fn filter(table: &(&ArchetypeId, &Table)) -> bool {
         !table.1.has_column::<T>()
     }
 
@@ -310,8 +308,8 @@ pub struct Is<T>(PhantomData<T>);
 impl<E, T: Archetype> Query<E> for Is<T> {
     type Output<'a> = ();
 
-    fn filter(table: &(&TypeId, &Table)) -> bool {
-        *table.0 == TypeId::of::<T>()
+    fn filter(table: &(&ArchetypeId, &Table)) -> bool {
+        *table.0 == ArchetypeId::of::<T>()
     }
 
     fn data<'a>(tables: &[&'a Table]) -> Self::Output<'a> {
@@ -319,17 +317,29 @@ impl<E, T: Archetype> Query<E> for Is<T> {
     }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ArchetypeId {
+    Static(TypeId),
+    Prefab(String),
+}
+
+impl ArchetypeId {
+    pub fn of<T: 'static>() -> Self {
+        Self::Static(TypeId::of::<T>())
+    }
+}
+
 pub struct TypedEntityId<T>(u32, PhantomData<T>);
-pub struct EntityId(u32, TypeId);
+pub struct EntityId(u32, ArchetypeId);
 
 impl<T: 'static> From<TypedEntityId<T>> for EntityId {
     fn from(value: TypedEntityId<T>) -> Self {
-        Self(value.0, TypeId::of::<T>())
+        Self(value.0, ArchetypeId::of::<T>())
     }
 }
 
 pub struct World<E> {
-    archetypes: HashMap<TypeId, Table>,
+    archetypes: HashMap<ArchetypeId, Table>,
     systems: Vec<Rc<dyn System<E>>>,
     resources: HashMap<TypeId, Rc<RefCell<dyn Any>>>,
 }
@@ -344,7 +354,7 @@ impl<E> Default for World<E> {
     }
 }
 
-impl<E> World<E> {
+impl<E: 'static> World<E> {
     pub fn new() -> Self {
         Self::default()
     }
@@ -371,20 +381,66 @@ impl<E> World<E> {
     }
 
     fn register<T: Archetype>(&mut self) {
+        let columns = T::columns();
+        for (i, ty) in columns.iter().enumerate() {
+            assert!(
+                !columns[..i].contains(ty),
+                "{} has more than one component of the same type",
+                type_name::<T>()
+            );
+        }
         self.archetypes
-            .insert(TypeId::of::<T>(), Table::new(&T::columns()));
+            .insert(ArchetypeId::of::<T>(), Table::new(&columns));
     }
 
     pub fn spawn<T: Archetype>(&mut self, entity: T) -> TypedEntityId<T> {
-        if !self.archetypes.contains_key(&TypeId::of::<T>()) {
+        if !self.archetypes.contains_key(&ArchetypeId::of::<T>()) {
             self.register::<T>();
         }
 
-        let store = self.archetypes.get_mut(&TypeId::of::<T>()).unwrap();
+        let store = self.archetypes.get_mut(&ArchetypeId::of::<T>()).unwrap();
         entity.add(store);
         TypedEntityId(store.len() as u32 - 1, PhantomData)
     }
 
+    pub fn spawn_prefab(&mut self, name: &str) -> Result<EntityId, PrefabError> {
+        self.spawn_prefab_with(name, serde_json::Value::Null)
+    }
+
+    pub fn spawn_prefab_with(
+        &mut self,
+        name: &str,
+        overrides: serde_json::Value,
+    ) -> Result<EntityId, PrefabError> {
+        let components = self
+            .get::<Prefabs<E>>()
+            .ok_or_else(|| PrefabError::UnknownPrefab(name.to_owned()))?
+            .instantiate(self, name, overrides)?;
+
+        let id = ArchetypeId::Prefab(name.to_owned());
+        let types: Vec<TypeId> = components.iter().map(|component| component.ty).collect();
+        let store = self
+            .archetypes
+            .entry(id.clone())
+            .or_insert_with(|| Table::new(&types));
+
+        // Components are matched to columns by type rather than position, as
+        // the prefab may have been added again with its components reordered
+        if store.columns.len() != types.len() || !types.iter().all(|ty| store.has_column_id(*ty)) {
+            return Err(PrefabError::ChangedPrefab(name.to_owned()));
+        }
+        store.length += 1;
+        for component in components {
+            let (_, column) = store
+                .columns
+                .iter()
+                .find(|(ty, _)| *ty == component.ty)
+                .unwrap();
+            component.push(&mut column.borrow_mut());
+        }
+        Ok(EntityId(store.len() as u32 - 1, id))
+    }
+
     pub fn query<Q: Query<E>>(&self) -> Q::Output<'_> {
         Q::data(
             &self
@@ -396,26 +452,34 @@ impl<E> World<E> {
         )
     }
 
-    /*
-    pub fn get_entities<T: Archetype>(&self) -> impl Iterator<Item = &T> {
-        let Some(table) = self.archetypes.get(&TypeId::of::<T>()) else {
-            return &[];
-        };
-
-        (0..table.borrow().len()).map(|index| T::from_components(components, indices))
+    /// Like `query`, but only over the instances of one prefab. Prefab
+    /// entities live in a table named after the prefab rather than one for
+    /// an `Archetype` type, so `iter_archetype` and `Is` never see them.
+    pub fn query_prefab<Q: Query<E>>(&self, name: &str) -> Q::Output<'_> {
+        let id = ArchetypeId::Prefab(name.to_owned());
+        Q::data(
+            &self
+                .archetypes
+                .iter()
+                .filter(|table| *table.0 == id && Q::filter(table))
+                .map(|(_, table)| table)
+                .collect::<Vec<_>>(),
+        )
     }
 
-    pub fn get_entities_mut<T: Archetype>(&self) -> Vec<T::Mut<'_>> {
-        let rows = &self.archetypes.get(&TypeId::of::<T>()).unwrap().rows;
-
-        let mut output = Vec::new();
-        for indices in rows {
-            output.push(T::from_components_mut(&self.components, indices));
-        }
+    pub fn iter_archetype<T: Archetype>(&self) -> impl Iterator<Item = T::Ref<'_>> {
+        self.archetypes
+            .get(&ArchetypeId::of::<T>())
+            .into_iter()
+            .flat_map(|table| T::iter(table))
+    }
 
-        output
+    pub fn iter_archetype_mut<T: Archetype>(&self) -> impl Iterator<Item = T::Mut<'_>> {
+        self.archetypes
+            .get(&ArchetypeId::of::<T>())
+            .into_iter()
+            .flat_map(|table| T::iter_mut(table))
     }
-    */
 
     pub fn get<T: Any>(&self) -> Option<Ref<'_, T>> {
         self.resources
@@ -437,7 +501,21 @@ impl<E> World<E> {
         })
     }
 
+    fn run_tasks(&mut self) {
+        let Some(completed) = self
+            .get_mut::<Tasks<E>>()
+            .map(|mut tasks| tasks.completed())
+        else {
+            return;
+        };
+
+        completed
+            .into_iter()
+            .for_each(|completion| completion(self))
+    }
+
     pub fn tick(&mut self) {
+        self.run_tasks();
         self.systems
             .clone()
             .into_iter()
@@ -0,0 +1,404 @@
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    path::Path,
+};
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+
+use crate::{Column, World};
+
+#[derive(Debug)]
+pub enum PrefabError {
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    UnknownPrefab(String),
+    UnknownComponent(String),
+    DuplicateComponent(String),
+    InvalidOverrides(String),
+    ChangedPrefab(String),
+    Component(String, Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl Display for PrefabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Failed to read prefab: {e}"),
+            Self::Json(e) => write!(f, "Failed to parse prefab: {e}"),
+            Self::UnknownPrefab(name) => write!(f, "No prefab named {name}"),
+            Self::UnknownComponent(name) => write!(f, "No component registered as {name}"),
+            Self::DuplicateComponent(name) => {
+                write!(
+                    f,
+                    "Component {name} has the same type as another in the prefab"
+                )
+            }
+            Self::InvalidOverrides(name) => {
+                write!(f, "Overrides for prefab {name} must be an object")
+            }
+            Self::ChangedPrefab(name) => write!(
+                f,
+                "Prefab {name} has different components to instances already spawned"
+            ),
+            Self::Component(name, e) => write!(f, "Failed to load component {name}: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for PrefabError {}
+
+impl From<std::io::Error> for PrefabError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+impl From<serde_json::Error> for PrefabError {
+    fn from(value: serde_json::Error) -> Self {
+        Self::Json(value)
+    }
+}
+
+type Loader<E> =
+    Box<dyn Fn(&World<E>, Value) -> Result<Box<dyn Any>, Box<dyn std::error::Error + Send + Sync>>>;
+
+struct Component<E> {
+    ty: TypeId,
+    push: fn(&mut Column, Box<dyn Any>),
+    loader: Loader<E>,
+}
+
+fn push<T: 'static>(column: &mut Column, component: Box<dyn Any>) {
+    column.push::<T>(*component.downcast().unwrap())
+}
+
+pub(crate) struct Instance {
+    pub ty: TypeId,
+    push: fn(&mut Column, Box<dyn Any>),
+    component: Box<dyn Any>,
+}
+
+impl Instance {
+    pub fn push(self, column: &mut Column) {
+        (self.push)(column, self.component)
+    }
+}
+
+fn merge(base: &mut Value, overrides: Value) {
+    match (base, overrides) {
+        (Value::Object(base), Value::Object(overrides)) => {
+            overrides
+                .into_iter()
+                .for_each(|(key, value)| match base.get_mut(&key) {
+                    Some(existing) => merge(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                })
+        }
+        (base, overrides) => *base = overrides,
+    }
+}
+
+/// Named entity templates, stored as JSON objects mapping component names
+/// to their values. Component names are resolved through the loaders
+/// registered with `with_component`/`with_loader`.
+pub struct Prefabs<E> {
+    components: HashMap<String, Component<E>>,
+    prefabs: HashMap<String, Map<String, Value>>,
+}
+
+impl<E> Default for Prefabs<E> {
+    fn default() -> Self {
+        Self {
+            components: HashMap::new(),
+            prefabs: HashMap::new(),
+        }
+    }
+}
+
+impl<E> Prefabs<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_component<T: DeserializeOwned + 'static>(self, name: &str) -> Self {
+        self.with_loader(name, |_, value| Ok(serde_json::from_value::<T>(value)?))
+    }
+
+    pub fn with_loader<T, F>(mut self, name: &str, loader: F) -> Self
+    where
+        T: 'static,
+        F: Fn(&World<E>, Value) -> Result<T, Box<dyn std::error::Error + Send + Sync>> + 'static,
+    {
+        self.components.insert(
+            name.to_owned(),
+            Component {
+                ty: TypeId::of::<T>(),
+                push: push::<T>,
+                loader: Box::new(move |world, value| {
+                    loader(world, value).map(|component| Box::new(component) as Box<dyn Any>)
+                }),
+            },
+        );
+        self
+    }
+
+    pub fn add(&mut self, name: &str, source: &str) -> Result<(), PrefabError> {
+        let prefab: Map<String, Value> = serde_json::from_str(source)?;
+
+        let mut types = HashSet::new();
+        for component in prefab.keys() {
+            let ty = self
+                .components
+                .get(component)
+                .ok_or_else(|| PrefabError::UnknownComponent(component.clone()))?
+                .ty;
+            if !types.insert(ty) {
+                return Err(PrefabError::DuplicateComponent(component.clone()));
+            }
+        }
+
+        self.prefabs.insert(name.to_owned(), prefab);
+        Ok(())
+    }
+
+    /// Adds every `.json` file in `directory`, named after its file stem.
+    pub fn load<P: AsRef<Path>>(&mut self, directory: P) -> Result<(), PrefabError> {
+        for entry in std::fs::read_dir(directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                let name = path.file_stem().unwrap().to_string_lossy().into_owned();
+                self.add(&name, &std::fs::read_to_string(&path)?)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.prefabs.contains_key(name)
+    }
+
+    pub(crate) fn instantiate(
+        &self,
+        world: &World<E>,
+        name: &str,
+        overrides: Value,
+    ) -> Result<Vec<Instance>, PrefabError> {
+        let prefab = self
+            .prefabs
+            .get(name)
+            .ok_or_else(|| PrefabError::UnknownPrefab(name.to_owned()))?;
+
+        let mut overrides = match overrides {
+            Value::Null => Map::new(),
+            Value::Object(overrides) => overrides,
+            _ => return Err(PrefabError::InvalidOverrides(name.to_owned())),
+        };
+        if let Some(component) = overrides.keys().find(|key| !prefab.contains_key(*key)) {
+            return Err(PrefabError::UnknownComponent(component.clone()));
+        }
+
+        prefab
+            .iter()
+            .map(|(component_name, value)| {
+                let mut value = value.clone();
+                if let Some(overrides) = overrides.remove(component_name) {
+                    merge(&mut value, overrides);
+                }
+
+                let component = self.components.get(component_name).unwrap();
+                Ok(Instance {
+                    ty: component.ty,
+                    push: component.push,
+                    component: (component.loader)(world, value)
+                        .map_err(|e| PrefabError::Component(component_name.clone(), e))?,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{ArchetypeId, Table};
+
+    #[derive(Debug, PartialEq)]
+    struct Name(String);
+    #[derive(Debug, PartialEq)]
+    struct Health(f32);
+
+    fn world(prefabs: &[(&str, &str)]) -> World<()> {
+        let mut registry = Prefabs::<()>::new()
+            .with_loader("name", |_, value| Ok(Name(serde_json::from_value(value)?)))
+            .with_loader("health", |_, value| {
+                Ok(Health(serde_json::from_value(value)?))
+            })
+            .with_component::<Value>("transform");
+        for (name, source) in prefabs {
+            registry.add(name, source).unwrap();
+        }
+        World::new().with_resource(registry)
+    }
+
+    fn table<'a>(world: &'a World<()>, name: &str) -> &'a Table {
+        &world.archetypes[&ArchetypeId::Prefab(name.to_owned())]
+    }
+
+    #[test]
+    fn overrides_merge_into_nested_fields() {
+        let mut world = world(&[(
+            "ore",
+            r#"{"name": "ore", "transform": {"position": [0, 0, 0], "scale": 2}}"#,
+        )]);
+        world
+            .spawn_prefab_with(
+                "ore",
+                json!({"transform": {"position": [1, 2, 3], "rotation": [0, 0, 0, 1]}}),
+            )
+            .unwrap();
+
+        let transforms = table(&world, "ore").column::<Value>().unwrap();
+        assert_eq!(
+            transforms[0],
+            json!({"position": [1, 2, 3], "scale": 2, "rotation": [0, 0, 0, 1]})
+        );
+        assert_eq!(
+            *table(&world, "ore").column::<Name>().unwrap(),
+            [Name("ore".into())]
+        );
+    }
+
+    #[test]
+    fn unknown_components_are_errors() {
+        let mut world = world(&[("ore", r#"{"name": "ore"}"#)]);
+        assert!(matches!(
+            world.spawn_prefab_with("ore", json!({"health": 10})),
+            Err(PrefabError::UnknownComponent(name)) if name == "health"
+        ));
+        assert!(matches!(
+            world.spawn_prefab_with("ore", json!([])),
+            Err(PrefabError::InvalidOverrides(_))
+        ));
+        assert!(matches!(
+            world.spawn_prefab("tree"),
+            Err(PrefabError::UnknownPrefab(_))
+        ));
+
+        let mut prefabs = Prefabs::<()>::new().with_component::<f32>("speed");
+        assert!(matches!(
+            prefabs.add("ore", r#"{"mass": 1}"#),
+            Err(PrefabError::UnknownComponent(name)) if name == "mass"
+        ));
+        // Nothing is spawned when an override fails
+        assert!(!world
+            .archetypes
+            .contains_key(&ArchetypeId::Prefab("ore".to_owned())));
+    }
+
+    #[test]
+    fn instances_share_a_table() {
+        let mut world = world(&[("ore", r#"{"name": "ore", "health": 10}"#)]);
+        let first = world.spawn_prefab("ore").unwrap();
+        let second = world
+            .spawn_prefab_with("ore", json!({"health": 5}))
+            .unwrap();
+
+        assert_eq!((first.0, second.0), (0, 1));
+        assert_eq!(first.1, second.1);
+        assert_eq!(world.archetypes.len(), 1);
+        let table = table(&world, "ore");
+        assert_eq!(table.len(), 2);
+        assert_eq!(
+            *table.column::<Health>().unwrap(),
+            [Health(10.0), Health(5.0)]
+        );
+    }
+
+    #[test]
+    fn querying_prefabs() {
+        let mut world = world(&[
+            ("ore", r#"{"name": "ore", "health": 10}"#),
+            ("tree", r#"{"name": "tree", "health": 20}"#),
+        ]);
+        world.spawn_prefab("ore").unwrap();
+        world.spawn_prefab("tree").unwrap();
+        world.spawn_prefab("ore").unwrap();
+
+        let (names, health) = world.query_prefab::<(&Name, &Health)>("ore");
+        assert_eq!(
+            names.iter().collect::<Vec<_>>(),
+            [&Name("ore".into()), &Name("ore".into())]
+        );
+        assert_eq!(
+            health.iter().collect::<Vec<_>>(),
+            [&Health(10.0), &Health(10.0)]
+        );
+
+        // Queries without a prefab name see every instance
+        let (health,) = world.query::<(&Health,)>();
+        let mut health: Vec<_> = health.iter().map(|health| health.0).collect();
+        health.sort_by(f32::total_cmp);
+        assert_eq!(health, [10.0, 10.0, 20.0]);
+
+        // Missing prefabs and components find nothing
+        let (names,) = world.query_prefab::<(&Name,)>("rock");
+        assert_eq!(names.iter().count(), 0);
+        let (transforms,) = world.query_prefab::<(&Value,)>("ore");
+        assert_eq!(transforms.iter().count(), 0);
+    }
+
+    #[test]
+    fn reordered_components_go_in_the_right_columns() {
+        let mut world = world(&[("ore", r#"{"name": "a", "health": 1}"#)]);
+        world.spawn_prefab("ore").unwrap();
+
+        // The same components listed the other way round, in the prefab and
+        // in the overrides. Whether that changes the order they're loaded in
+        // depends on serde_json's `preserve_order` feature, so it can't matter
+        world
+            .get_mut::<Prefabs<()>>()
+            .unwrap()
+            .add("ore", r#"{"health": 2, "name": "b"}"#)
+            .unwrap();
+        world
+            .spawn_prefab_with("ore", json!({"health": 3, "name": "c"}))
+            .unwrap();
+        world.spawn_prefab("ore").unwrap();
+
+        let table = table(&world, "ore");
+        assert_eq!(
+            *table.column::<Name>().unwrap(),
+            [Name("a".into()), Name("c".into()), Name("b".into())]
+        );
+        assert_eq!(
+            *table.column::<Health>().unwrap(),
+            [Health(1.0), Health(3.0), Health(2.0)]
+        );
+    }
+
+    #[test]
+    fn changed_components_are_an_error() {
+        let mut world = world(&[("ore", r#"{"name": "a", "health": 1}"#)]);
+        world.spawn_prefab("ore").unwrap();
+        world
+            .get_mut::<Prefabs<()>>()
+            .unwrap()
+            .add("ore", r#"{"name": "a"}"#)
+            .unwrap();
+        assert!(matches!(
+            world.spawn_prefab("ore"),
+            Err(PrefabError::ChangedPrefab(_))
+        ));
+        assert_eq!(table(&world, "ore").len(), 1);
+    }
+}
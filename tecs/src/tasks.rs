@@ -0,0 +1,221 @@
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use tokio::runtime::Handle;
+
+use crate::World;
+
+type Output = Box<dyn Any + Send>;
+// `None` for futures that panicked or were dropped before finishing
+type Message = (u64, Option<Output>);
+type Callback<E> = Box<dyn FnOnce(&mut World<E>, Output)>;
+pub(crate) type Completion<E> = Box<dyn FnOnce(&mut World<E>)>;
+
+/// Runs futures on a tokio runtime and hands their results back to the world.
+///
+/// Callbacks never leave the main thread, so they can capture anything a
+/// system could, and are run by `World::tick` once their future resolves.
+pub struct Tasks<E> {
+    runtime: Handle,
+    next: u64,
+    callbacks: HashMap<u64, Callback<E>>,
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+}
+
+// Reports back when the task is done with, however that happens, so the
+// callback doesn't stay pending forever
+struct Report {
+    id: u64,
+    output: Option<Output>,
+    sender: Sender<Message>,
+}
+
+impl Report {
+    fn finish(mut self, output: Output) {
+        self.output = Some(output);
+    }
+}
+
+impl Drop for Report {
+    fn drop(&mut self) {
+        // The receiver only goes away with the world, at which point nobody cares
+        let _ = self.sender.send((self.id, self.output.take()));
+    }
+}
+
+impl<E: 'static> Tasks<E> {
+    /// Panics if called outside of a tokio runtime, see `Handle::current`.
+    pub fn new() -> Self {
+        Self::with_handle(Handle::current())
+    }
+
+    pub fn with_handle(runtime: Handle) -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            runtime,
+            next: 0,
+            callbacks: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    pub fn spawn<F, C>(&mut self, future: F, callback: C)
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+        C: FnOnce(&mut World<E>, F::Output) + 'static,
+    {
+        let id = self.next;
+        self.next += 1;
+
+        self.callbacks.insert(
+            id,
+            Box::new(move |world, output| callback(world, *output.downcast().unwrap())),
+        );
+
+        let report = Report {
+            id,
+            output: None,
+            sender: self.sender.clone(),
+        };
+        self.runtime.spawn(async move {
+            let output = future.await;
+            report.finish(Box::new(output));
+        });
+    }
+
+    pub fn pending(&self) -> usize {
+        self.callbacks.len()
+    }
+
+    pub(crate) fn completed(&mut self) -> Vec<Completion<E>> {
+        self.receiver
+            .try_iter()
+            .filter_map(|(id, output)| {
+                let callback = self.callbacks.remove(&id)?;
+                let output = output?;
+                Some(Box::new(move |world: &mut World<E>| callback(world, output)) as Completion<E>)
+            })
+            .collect()
+    }
+}
+
+impl<E: 'static> Default for Tasks<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        cell::RefCell,
+        rc::Rc,
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+    };
+
+    use tokio::runtime::{Builder, Runtime};
+
+    use super::*;
+
+    fn runtime() -> Runtime {
+        Builder::new_current_thread().build().unwrap()
+    }
+
+    // Lets spawned tasks make progress without finishing anything here
+    fn run(runtime: &Runtime) {
+        runtime.block_on(async {
+            for _ in 0..16 {
+                tokio::task::yield_now().await;
+            }
+        });
+    }
+
+    fn gate() -> (Arc<AtomicBool>, impl Future<Output = ()>) {
+        let open = Arc::new(AtomicBool::new(false));
+        let future = {
+            let open = open.clone();
+            async move {
+                while !open.load(Ordering::SeqCst) {
+                    tokio::task::yield_now().await;
+                }
+            }
+        };
+        (open, future)
+    }
+
+    #[test]
+    fn callbacks_run_on_tick_in_completion_order() {
+        let runtime = runtime();
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let mut tasks = Tasks::<()>::with_handle(runtime.handle().clone());
+        let gates: Vec<_> = (0..3)
+            .map(|i| {
+                let (open, future) = gate();
+                let order = order.clone();
+                tasks.spawn(
+                    async move {
+                        future.await;
+                        i
+                    },
+                    move |_, i| order.borrow_mut().push(i),
+                );
+                open
+            })
+            .collect();
+        let mut world = World::<()>::new().with_resource(tasks);
+
+        for i in [2, 0, 1] {
+            gates[i].store(true, Ordering::SeqCst);
+            run(&runtime);
+            // Finished, but nothing runs until the world ticks
+            assert_eq!(
+                order.borrow().len(),
+                [2, 0, 1].iter().position(|j| *j == i).unwrap()
+            );
+            world.tick();
+        }
+        assert_eq!(*order.borrow(), [2, 0, 1]);
+        assert_eq!(world.get::<Tasks<()>>().unwrap().pending(), 0);
+    }
+
+    #[test]
+    fn panicking_task_is_no_longer_pending() {
+        let runtime = runtime();
+        let mut tasks = Tasks::<()>::with_handle(runtime.handle().clone());
+        tasks.spawn(async { panic!("task failed") }, |_, ()| unreachable!());
+        tasks.spawn(async { 1 }, |_, _| ());
+        let mut world = World::<()>::new().with_resource(tasks);
+
+        run(&runtime);
+        world.tick();
+        assert_eq!(world.get::<Tasks<()>>().unwrap().pending(), 0);
+    }
+
+    #[test]
+    fn dropped_task_is_no_longer_pending() {
+        let runtime = runtime();
+        let mut tasks = Tasks::<()>::with_handle(runtime.handle().clone());
+        let (_, future) = gate();
+        tasks.spawn(future, |_, ()| unreachable!());
+        let mut world = World::<()>::new().with_resource(tasks);
+
+        run(&runtime);
+        world.tick();
+        assert_eq!(world.get::<Tasks<()>>().unwrap().pending(), 1);
+
+        // Shutting the runtime down drops the unfinished future
+        drop(runtime);
+        world.tick();
+        assert_eq!(world.get::<Tasks<()>>().unwrap().pending(), 0);
+    }
+}
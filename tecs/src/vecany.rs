@@ -9,12 +9,7 @@ pub struct VecAny {
 
 impl VecAny {
     pub fn new<T: 'static>() -> Self {
-        Self {
-            ptr: unsafe { Some(std::alloc::alloc(Layout::new::<T>())) },
-            len: 0,
-            cap: 0,
-            ty: TypeId::of::<T>(),
-        }
+        Self::new_uninit(TypeId::of::<T>())
     }
 
     pub fn new_uninit(ty: TypeId) -> Self {
@@ -64,30 +59,33 @@ impl VecAny {
     }
 
     pub fn push<T: 'static>(&mut self, item: T) {
-        if self.ptr.is_none() {
-            self.ptr = Some(unsafe { std::alloc::alloc(Layout::new::<T>()) })
-        }
-
         if self.ty != TypeId::of::<T>() {
             return;
         }
 
         if self.len == self.cap {
-            self.cap *= 2;
-
-            self.ptr = Some(unsafe {
-                std::alloc::realloc(
-                    self.ptr.unwrap(),
-                    Layout::new::<T>(),
-                    self.cap * std::mem::size_of::<T>(),
-                )
-            })
+            let cap = (self.cap * 2).max(1);
+            let layout = Layout::array::<T>(cap).unwrap();
+
+            self.ptr = Some(if layout.size() == 0 {
+                std::ptr::NonNull::<T>::dangling().as_ptr().cast()
+            } else {
+                match self.ptr {
+                    Some(ptr) if self.cap > 0 => unsafe {
+                        std::alloc::realloc(
+                            ptr,
+                            Layout::array::<T>(self.cap).unwrap(),
+                            layout.size(),
+                        )
+                    },
+                    _ => unsafe { std::alloc::alloc(layout) },
+                }
+            });
+            self.cap = cap;
         }
 
+        unsafe { self.ptr.unwrap().cast::<T>().add(self.len).write(item) };
         self.len += 1;
-        unsafe {
-            std::slice::from_raw_parts_mut(self.ptr.unwrap().cast(), self.len)[self.len - 1] = item;
-        }
     }
 
     pub fn len(&self) -> usize {
@@ -1,9 +1,11 @@
-use std::path::Path;
+use std::{collections::HashMap, path::Path};
 
-use anyhow::Result;
-use glam::{Vec3, Vec4};
+use anyhow::{anyhow, Result};
+use glam::Vec4;
 use gltf::Glb;
-use hephaestus::{buffer::Static, BufferUsageFlags, Context, VkResult};
+use hephaestus::{buffer::Static, layout::Std140, BufferUsageFlags, Context, VkResult};
+use log::debug;
+use thanatos_macros::Std140;
 
 use crate::graphics::{Renderer, Vertex};
 
@@ -13,59 +15,98 @@ pub struct Mesh {
     pub num_indices: u32,
 }
 
-impl Mesh {
-    pub fn load<T: AsRef<Path>>(path: T, renderer: &Renderer) -> Result<Self> {
-        let model = Glb::load(&std::fs::read(path).unwrap()).unwrap();
-
-        let positions: Vec<Vec3> = bytemuck::cast_slice::<u8, f32>(
-            &model.gltf.meshes[0].primitives[0]
-                .get_attribute_data(&model, "POSITION")
-                .unwrap(),
-        )
-        .chunks(3)
-        .map(|pos| Vec3::from_slice(pos))
-        .collect();
-
-        let normals: Vec<Vec3> = bytemuck::cast_slice::<u8, f32>(
-            &model.gltf.meshes[0].primitives[0]
-                .get_attribute_data(&model, "NORMAL")
-                .unwrap(),
-        )
-        .chunks(3)
-        .map(|pos| Vec3::from_slice(pos))
-        .collect();
+/// A mesh's vertices and indices, processed and ready to upload.
+pub struct MeshData {
+    pub vertices: Vec<Vertex>,
+    pub indices: Vec<u32>,
+}
+
+impl MeshData {
+    pub fn from_glb(model: &Glb) -> Result<Self> {
+        let primitive = model
+            .gltf
+            .meshes
+            .first()
+            .and_then(|mesh| mesh.primitives.first())
+            .ok_or_else(|| anyhow!("Model has no meshes"))?;
+
+        let positions = primitive
+            .attribute(model, "POSITION")?
+            .ok_or_else(|| anyhow!("Mesh has no positions"))?
+            .read_vec3_f32(model)?;
+        let indices = primitive.triangle_indices(model)?;
+
+        // Without normals every triangle gets its own vertices with its
+        // face normal, as the spec asks for
+        let (positions, normals, indices) = match primitive.attribute(model, "NORMAL")? {
+            Some(normals) => (positions, normals.read_vec3_f32(model)?, indices),
+            None => (
+                gltf::unweld(&positions, &indices)?,
+                gltf::flat_normals(&positions, &indices)?,
+                (0..indices.len() as u32).collect(),
+            ),
+        };
 
         let vertices: Vec<Vertex> = positions
             .into_iter()
             .zip(normals.into_iter())
             .map(|(position, normal)| Vertex { position, normal })
             .collect();
+        let gltf::Optimized {
+            vertices,
+            indices,
+            acmr_before,
+            acmr_after,
+        } = gltf::optimize(&vertices, &indices, |vertex| vertex.position)?;
+        debug!("Optimized mesh, ACMR {acmr_before:.3} -> {acmr_after:.3}");
+
+        Ok(Self { vertices, indices })
+    }
+}
 
-        let indices: Vec<u32> = model.gltf.meshes[0].primitives[0]
-            .get_indices_data(&model)
-            .unwrap();
-
+impl Mesh {
+    pub fn load(data: &MeshData, renderer: &Renderer) -> Result<Self> {
         let vertex_buffer = Static::new(
             &renderer.ctx,
-            bytemuck::cast_slice::<Vertex, u8>(&vertices),
+            bytemuck::cast_slice::<Vertex, u8>(&data.vertices),
             BufferUsageFlags::VERTEX_BUFFER,
         )?;
         let index_buffer = Static::new(
             &renderer.ctx,
-            bytemuck::cast_slice::<u32, u8>(&indices),
+            bytemuck::cast_slice::<u32, u8>(&data.indices),
             BufferUsageFlags::INDEX_BUFFER,
         )?;
 
         Ok(Mesh {
             vertex_buffer,
             index_buffer,
-            num_indices: indices.len() as u32,
+            num_indices: data.indices.len() as u32,
         })
     }
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+fn open_glb(path: &Path) -> Result<Glb> {
+    let glb = Glb::open(path)?;
+    let issues = gltf::validate(&glb);
+    if !issues.is_empty() {
+        let issues: Vec<String> = issues.iter().map(ToString::to_string).collect();
+        return Err(anyhow!(
+            "{} is invalid:\n{}",
+            path.display(),
+            issues.join("\n")
+        ));
+    }
+    Ok(glb)
+}
+
+/// Loads the first mesh in a `.glb`. Reading, validating and optimizing it
+/// all happen off the main thread, leaving only the upload to the caller.
+pub async fn load_mesh<T: AsRef<Path>>(path: T) -> Result<MeshData> {
+    let path = path.as_ref().to_owned();
+    tokio::task::spawn_blocking(move || MeshData::from_glb(&open_glb(&path)?)).await?
+}
+
+#[derive(Clone, Copy, Debug, Std140)]
 pub struct MaterialData {
     pub colour: Vec4,
 }
@@ -76,8 +117,11 @@ pub struct Material {
 
 impl Material {
     pub fn load(material: MaterialData, renderer: &Renderer) -> Result<Self> {
-        let contents = bytemuck::bytes_of(&material);
-        let buffer = Static::new(&renderer.ctx, &contents, BufferUsageFlags::UNIFORM_BUFFER)?;
+        let buffer = Static::new(
+            &renderer.ctx,
+            &material.as_std140(),
+            BufferUsageFlags::UNIFORM_BUFFER,
+        )?;
         Ok(Self { buffer })
     }
 }
@@ -90,6 +134,7 @@ pub struct MaterialId(usize);
 #[derive(Default)]
 pub struct Manager {
     meshes: Vec<Mesh>,
+    mesh_names: HashMap<String, MeshId>,
     materials: Vec<Material>,
 }
 
@@ -107,6 +152,14 @@ impl Manager {
         self.meshes.get(id.0)
     }
 
+    pub fn name_mesh(&mut self, name: &str, id: MeshId) {
+        self.mesh_names.insert(name.to_owned(), id);
+    }
+
+    pub fn find_mesh(&self, name: &str) -> Option<MeshId> {
+        self.mesh_names.get(name).copied()
+    }
+
     pub fn add_material(&mut self, material: Material) -> MaterialId {
         self.materials.push(material);
         MaterialId(self.materials.len() - 1)
@@ -1,7 +1,13 @@
 use glam::{Mat4, Vec3};
+use thanatos_macros::{system, Std140};
 
 use crate::{event::Event, window::Window, World};
 
+#[derive(Std140)]
+pub struct CameraData {
+    pub view_proj: Mat4,
+}
+
 pub struct Camera {
     pub eye: Vec3,
     pub direction: Vec3,
@@ -28,12 +34,10 @@ impl Camera {
     }
 }
 
+#[system(events(Event::Resized(_)))]
 pub fn handle_resize(world: &mut World, event: &Event) {
-    match event {
-        Event::Resized(new_size) => {
-            let mut camera = world.get_mut::<Camera>().unwrap();
-            camera.aspect = new_size.width as f32 / new_size.height as f32;
-        }
-        _ => (),
+    if let Event::Resized(new_size) = event {
+        let mut camera = world.get_mut::<Camera>().unwrap();
+        camera.aspect = new_size.width as f32 / new_size.height as f32;
     }
 }
@@ -1,43 +1,40 @@
-use std::{collections::VecDeque, mem::size_of};
+use std::{collections::VecDeque, error::Error};
 
 use crate::{
     assets::{self, MeshId},
-    camera::Camera,
+    camera::{Camera, CameraData},
     window::Window,
     World,
 };
-use bytemuck::offset_of;
 use glam::{Vec2, Vec3};
 use hephaestus::{
     buffer::Static,
     command, descriptor,
     image::{Image, ImageView},
+    layout::Std140,
     pipeline::{
         self, clear_colour, clear_depth, Framebuffer, ImageLayout, PipelineBindPoint, RenderPass,
         ShaderModule, Subpass, Viewport,
     },
     task::{Fence, Semaphore, SubmitInfo, Task},
-    vertex::{self, AttributeType},
+    vertex::Layout,
     BufferUsageFlags, ClearColorValue, ClearValue, Context, DescriptorType, Extent2D, Format,
     ImageAspectFlags, ImageUsageFlags, PipelineStageFlags, VkResult,
 };
 use log::info;
+use serde::Deserialize;
+use serde_json::Value;
+use thanatos_macros::{system, VertexLayout};
 
 #[repr(C)]
-#[derive(Clone, Copy, Debug, PartialEq, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(
+    Clone, Copy, Debug, PartialEq, Default, bytemuck::Pod, bytemuck::Zeroable, VertexLayout,
+)]
 pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
 }
 
-impl Vertex {
-    pub fn info() -> vertex::Info {
-        vertex::Info::new(size_of::<Self>())
-            .attribute(AttributeType::Vec3, 0)
-            .attribute(AttributeType::Vec3, offset_of!(Vertex, normal))
-    }
-}
-
 struct Frame {
     task: Task,
     cmd: command::Buffer,
@@ -60,6 +57,23 @@ pub struct RenderObject {
     pub mesh: MeshId,
 }
 
+impl RenderObject {
+    pub fn load(world: &World, value: Value) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        #[derive(Deserialize)]
+        struct Definition {
+            mesh: String,
+        }
+
+        let definition: Definition = serde_json::from_value(value)?;
+        let mesh = world
+            .get::<assets::Manager>()
+            .unwrap()
+            .find_mesh(&definition.mesh)
+            .ok_or_else(|| format!("No mesh named {}", definition.mesh))?;
+        Ok(Self { mesh })
+    }
+}
+
 pub struct Renderer {
     pub ctx: Context,
     render_pass: RenderPass,
@@ -253,6 +267,7 @@ impl Renderer {
     }
 }
 
+#[system]
 pub fn draw(world: &mut World) {
     let mut renderer = world.get_mut::<Renderer>().unwrap();
     if renderer.tasks.len() > Renderer::FRAMES_IN_FLIGHT {
@@ -288,7 +303,10 @@ pub fn draw(world: &mut World) {
     let camera = world.get::<Camera>().unwrap();
     let camera_buffer = Static::new(
         &renderer.ctx,
-        bytemuck::cast_slice::<f32, u8>(&camera.get_matrix().to_cols_array()),
+        &CameraData {
+            view_proj: camera.get_matrix(),
+        }
+        .as_std140(),
         BufferUsageFlags::UNIFORM_BUFFER,
     )
     .unwrap();
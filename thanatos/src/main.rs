@@ -12,20 +12,9 @@ use assets::Mesh;
 use event::Event;
 use glam::{Quat, Vec3};
 use graphics::{RenderObject, Renderer};
-use tecs::impl_archetype;
-use thanatos_macros::Archetype;
+use log::error;
 use window::{Keyboard, Mouse};
 
-#[derive(Archetype)]
-struct CopperOre {
-    render: RenderObject,
-}
-
-#[derive(Archetype)]
-struct Tree {
-    render: RenderObject,
-}
-
 #[derive(Clone, Debug)]
 pub struct Clock {
     frame_delta: Duration,
@@ -49,6 +38,38 @@ pub enum State {
 }
 
 pub type World = tecs::World<Event>;
+pub type Tasks = tecs::Tasks<Event>;
+
+pub type Prefabs = tecs::Prefabs<Event>;
+
+fn spawn_with_mesh(world: &mut World, prefab: &'static str, path: &'static str) {
+    world
+        .get_mut::<Tasks>()
+        .unwrap()
+        .spawn(assets::load_mesh(path), move |world, data| {
+            // A broken asset only loses its own entity, the game carries on
+            let mesh = data.and_then(|data| {
+                let renderer = world.get::<Renderer>().unwrap();
+                Mesh::load(&data, &renderer)
+            });
+            let mesh = match mesh {
+                Ok(mesh) => mesh,
+                Err(error) => {
+                    error!("Couldn't load {path}: {error:#}");
+                    return;
+                }
+            };
+            {
+                let mut assets = world.get_mut::<assets::Manager>().unwrap();
+                let mesh = assets.add_mesh(mesh);
+                assets.name_mesh(prefab, mesh);
+            }
+            // Instances are found with `World::query_prefab(prefab)`
+            if let Err(error) = world.spawn_prefab(prefab) {
+                error!("Couldn't spawn {prefab}: {error}");
+            }
+        });
+}
 
 #[tokio::main]
 async fn main() -> Result<()> {
@@ -59,15 +80,17 @@ async fn main() -> Result<()> {
     let renderer = Renderer::new(&window)?;
     let camera = Camera::new(&window);
 
-    let mut assets = assets::Manager::new();
-    let copper_ore = assets.add_mesh(Mesh::load("assets/meshes/copper_ore.glb", &renderer)?);
-    let tree = assets.add_mesh(Mesh::load("assets/meshes/tree.glb", &renderer)?);
+    let assets = assets::Manager::new();
+    let mut prefabs = Prefabs::new().with_loader("render", RenderObject::load);
+    prefabs.load("assets/prefabs")?;
     let mut world = World::new()
         .with_resource(State::Running)
         .with_resource(window)
         .with_resource(renderer)
         .with_resource(camera)
         .with_resource(assets)
+        .with_resource(Tasks::new())
+        .with_resource(prefabs)
         .with_resource(Mouse::default())
         .with_resource(Keyboard::default())
         .with_resource(Clock {
@@ -75,10 +98,10 @@ async fn main() -> Result<()> {
             start: Instant::now(),
             last: Instant::now(),
         })
-        .with_ticker(window::clear_mouse_delta)
-        .with_ticker(window::poll_events)
-        .with_handler(camera::handle_resize)
-        .with_ticker(graphics::draw)
+        .with_system(window::clear_mouse_delta)
+        .with_system(window::poll_events)
+        .with_system(camera::handle_resize)
+        .with_system(graphics::draw)
         .with_ticker(|world| {
             let clock = world.get::<Clock>().unwrap();
             println!("FPS: {}", 1.0 / clock.frame_delta.as_secs_f32());
@@ -91,12 +114,8 @@ async fn main() -> Result<()> {
             _ => (),
         });
 
-    world.spawn(CopperOre {
-        render: RenderObject { mesh: copper_ore },
-    });
-    world.spawn(Tree {
-        render: RenderObject { mesh: tree },
-    });
+    spawn_with_mesh(&mut world, "copper_ore", "assets/meshes/copper_ore.glb");
+    spawn_with_mesh(&mut world, "tree", "assets/meshes/tree.glb");
 
     loop {
         if let State::Stopped = *world.get::<State>().unwrap() {
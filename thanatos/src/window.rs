@@ -1,6 +1,7 @@
 use std::{collections::HashSet, sync::Arc};
 
 use glam::Vec2;
+use thanatos_macros::system;
 use winit::{
     event::{ElementState, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
@@ -17,6 +18,7 @@ pub struct Mouse {
     pub delta: Vec2,
 }
 
+#[system]
 pub fn clear_mouse_delta(world: &mut World) {
     let mut mouse = world.get_mut::<Mouse>().unwrap();
     mouse.delta = Vec2::ZERO;
@@ -67,6 +69,7 @@ impl Window {
     }
 }
 
+#[system]
 pub fn poll_events(world: &mut World) {
     let mut events = Vec::new();
     {
@@ -0,0 +1,306 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_quote, Attribute, Data, DeriveInput, Error, Fields, Ident, Member, Path, Result, Type,
+    Visibility,
+};
+
+pub struct Component {
+    pub member: Member,
+    pub ty: Type,
+    pub vis: Visibility,
+    pub docs: Vec<Attribute>,
+}
+
+fn parse_crate(attrs: &[Attribute]) -> Result<Path> {
+    let mut krate = parse_quote!(::tecs);
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("archetype"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.parse::<syn::LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unknown archetype attribute, expected `crate = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(krate)
+}
+
+fn is_skipped(attrs: &[Attribute]) -> Result<bool> {
+    let mut skip = false;
+    for attr in attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("archetype"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("skip") {
+                skip = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown archetype attribute, expected `skip`"))
+            }
+        })?;
+    }
+    Ok(skip)
+}
+
+// Types are compared by their full path, so `physics::Position` and
+// `render::Position` are different. A type named two ways, like `Vec3` and
+// `glam::Vec3`, can't be spotted here, so `World` checks the columns' type
+// ids when the archetype is first spawned
+fn type_key(ty: &Type) -> String {
+    match ty {
+        Type::Group(group) => type_key(&group.elem),
+        Type::Paren(paren) => type_key(&paren.elem),
+        _ => ty.to_token_stream().to_string(),
+    }
+}
+
+pub fn components(input: &DeriveInput) -> Result<Vec<Component>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "Archetype can only be derived for structs",
+        ));
+    };
+
+    if let Some(lifetime) = input.generics.lifetimes().next() {
+        return Err(Error::new_spanned(
+            lifetime,
+            "archetypes are stored in the world so can't borrow anything",
+        ));
+    }
+
+    let mut components: Vec<Component> = Vec::new();
+    for (i, field) in data.fields.iter().enumerate() {
+        if is_skipped(&field.attrs)? {
+            continue;
+        }
+
+        let ty = type_key(&field.ty);
+        if components
+            .iter()
+            .any(|component| type_key(&component.ty) == ty)
+        {
+            return Err(Error::new_spanned(
+                &field.ty,
+                "each component of an archetype needs a distinct type",
+            ));
+        }
+
+        components.push(Component {
+            member: field
+                .ident
+                .clone()
+                .map(Member::Named)
+                .unwrap_or_else(|| Member::Unnamed(i.into())),
+            ty: field.ty.clone(),
+            vis: field.vis.clone(),
+            docs: field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("doc"))
+                .cloned()
+                .collect(),
+        });
+    }
+
+    Ok(components)
+}
+
+fn mentions(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        proc_macro2::TokenTree::Ident(other) => other == *ident,
+        proc_macro2::TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
+
+struct View {
+    name: Ident,
+    definition: TokenStream,
+    members: Vec<Member>,
+    marker: Option<Member>,
+}
+
+fn view(input: &DeriveInput, components: &[Component], mutable: bool) -> View {
+    let name = &input.ident;
+    let vis = &input.vis;
+    let view = format_ident!("{}{}", name, if mutable { "Mut" } else { "Ref" });
+    let doc = format!(
+        "{} view of a [`{}`] stored in a `tecs::World`.",
+        if mutable { "Mutable" } else { "Shared" },
+        name
+    );
+    let guard = if mutable {
+        quote!(::std::cell::RefMut)
+    } else {
+        quote!(::std::cell::Ref)
+    };
+
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, parse_quote!('a));
+    let (generics, _, where_clause) = generics.split_for_impl();
+
+    let unused: Vec<_> = input
+        .generics
+        .type_params()
+        .map(|param| &param.ident)
+        .filter(|param| {
+            !components
+                .iter()
+                .any(|component| mentions(component.ty.to_token_stream(), param))
+        })
+        .collect();
+    let marker_ty = (components.is_empty() || !unused.is_empty())
+        .then(|| quote!(::std::marker::PhantomData<&'a (#(#unused,)*)>));
+
+    let tuple = matches!(
+        input.data,
+        Data::Struct(ref data) if matches!(data.fields, Fields::Unnamed(_))
+    );
+    let (members, marker) = if tuple {
+        (
+            (0..components.len())
+                .map(|i| Member::Unnamed(i.into()))
+                .collect(),
+            marker_ty
+                .as_ref()
+                .map(|_| Member::Unnamed(components.len().into())),
+        )
+    } else {
+        (
+            components
+                .iter()
+                .map(|component| component.member.clone())
+                .collect::<Vec<_>>(),
+            marker_ty
+                .as_ref()
+                .map(|_| Member::Named(format_ident!("_marker"))),
+        )
+    };
+
+    let fields = components.iter().zip(&members).map(|(component, member)| {
+        let Component { ty, vis, docs, .. } = component;
+        let name = (!tuple).then(|| quote!(#member:));
+        quote!(#(#docs)* #vis #name #guard<'a, #ty>)
+    });
+    let definition = if tuple {
+        let marker = marker_ty.iter();
+        quote! {
+            #[doc = #doc]
+            #vis struct #view #generics (#(#fields,)* #(#marker)*) #where_clause;
+        }
+    } else {
+        let marker = marker_ty.iter().map(|ty| quote!(_marker: #ty));
+        quote! {
+            #[doc = #doc]
+            #vis struct #view #generics #where_clause {
+                #(#fields,)*
+                #(#marker)*
+            }
+        }
+    };
+
+    View {
+        name: view,
+        definition,
+        members,
+        marker,
+    }
+}
+
+fn view_iter(view: &View, types: &[&Type], mutable: bool) -> TokenStream {
+    let name = &view.name;
+    let members = &view.members;
+    let marker = view
+        .marker
+        .iter()
+        .map(|marker| quote!(#marker: ::std::marker::PhantomData,));
+    let rows = if mutable {
+        quote!(rows_mut)
+    } else {
+        quote!(rows)
+    };
+    let columns: Vec<_> = (0..types.len())
+        .map(|i| format_ident!("column_{}", i))
+        .collect();
+
+    quote! {
+        #(let mut #columns = table.#rows::<#types>().unwrap();)*
+        (0..table.len()).map(move |_| #name {
+            #(#members: #columns.next().unwrap(),)*
+            #(#marker)*
+        })
+    }
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let krate = parse_crate(&input.attrs)?;
+    let components = components(&input)?;
+    let name = &input.ident;
+    let members: Vec<_> = components
+        .iter()
+        .map(|component| &component.member)
+        .collect();
+    let types: Vec<_> = components.iter().map(|component| &component.ty).collect();
+
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    where_clause.predicates.push(parse_quote!(Self: 'static));
+    types
+        .iter()
+        .for_each(|ty| where_clause.predicates.push(parse_quote!(#ty: 'static)));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let shared = view(&input, &components, false);
+    let mutable = view(&input, &components, true);
+    let (shared_name, shared_definition) = (&shared.name, &shared.definition);
+    let (mutable_name, mutable_definition) = (&mutable.name, &mutable.definition);
+    let iter = view_iter(&shared, &types, false);
+    let iter_mut = view_iter(&mutable, &types, true);
+
+    let mut view_generics = input.generics.clone();
+    view_generics.params.insert(0, parse_quote!('a));
+    let (_, view_generics, _) = view_generics.split_for_impl();
+
+    let add = if components.is_empty() {
+        quote!()
+    } else {
+        quote! {
+            let mut columns = table.columns_mut();
+            #(columns.next().unwrap().push::<#types>(self.#members);)*
+        }
+    };
+
+    Ok(quote! {
+        #shared_definition
+        #mutable_definition
+
+        impl #impl_generics #krate::Archetype for #name #ty_generics #where_clause {
+            type Ref<'a> = #shared_name #view_generics;
+            type Mut<'a> = #mutable_name #view_generics;
+
+            fn columns() -> ::std::vec::Vec<::std::any::TypeId> {
+                ::std::vec![#(::std::any::TypeId::of::<#types>()),*]
+            }
+
+            fn add(self, table: &mut #krate::Table) {
+                table.length += 1;
+                #add
+            }
+
+            fn iter(table: &#krate::Table) -> impl ::std::iter::Iterator<Item = Self::Ref<'_>> {
+                #iter
+            }
+
+            fn iter_mut(table: &#krate::Table) -> impl ::std::iter::Iterator<Item = Self::Mut<'_>> {
+                #iter_mut
+            }
+        }
+    })
+}
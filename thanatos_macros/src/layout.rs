@@ -0,0 +1,113 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, quote_spanned};
+use syn::{
+    parse_quote, spanned::Spanned, Attribute, Data, DeriveInput, Error, Member, Path, Result,
+};
+
+#[derive(Clone, Copy)]
+pub enum Kind {
+    Std140,
+    Std430,
+}
+
+fn parse_crate(attrs: &[Attribute], name: &str) -> Result<Path> {
+    let mut krate = parse_quote!(::hephaestus);
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident(name)) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.parse::<syn::LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error(format!(
+                    "unknown {name} attribute, expected `crate = \"...\"`"
+                )))
+            }
+        })?;
+    }
+    Ok(krate)
+}
+
+pub fn derive(input: DeriveInput, kind: Kind) -> Result<TokenStream> {
+    let (name, write, min_align) = match kind {
+        Kind::Std140 => ("std140", format_ident!("write_std140"), 16usize),
+        Kind::Std430 => ("std430", format_ident!("write_std430"), 1usize),
+    };
+    let trait_name = format_ident!("{}", name.replace("std", "Std"));
+    let krate = parse_crate(&input.attrs, name)?;
+
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            format!("{trait_name} can only be derived for structs"),
+        ));
+    };
+    if data.fields.is_empty() {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "GLSL blocks need at least one member",
+        ));
+    }
+
+    let layout = quote!(#krate::layout);
+    let members: Vec<_> = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            field
+                .ident
+                .clone()
+                .map(Member::Named)
+                .unwrap_or_else(|| Member::Unnamed(i.into()))
+        })
+        .collect();
+    let types: Vec<_> = data.fields.iter().map(|field| &field.ty).collect();
+    // Spanned so an unsupported field is reported at its type
+    let fields: Vec<_> = types
+        .iter()
+        .map(|ty| quote_spanned!(ty.span()=> <#ty as #layout::#trait_name>))
+        .collect();
+
+    // Bounds on concrete field types would be trivially false rather than
+    // an error, so only generic structs get them
+    let mut generics = input.generics.clone();
+    if input.generics.type_params().next().is_some() {
+        let where_clause = generics.make_where_clause();
+        types.iter().for_each(|ty| {
+            where_clause
+                .predicates
+                .push(parse_quote!(#ty: #layout::#trait_name))
+        });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let ident = &input.ident;
+
+    Ok(quote! {
+        impl #impl_generics #layout::#trait_name for #ident #ty_generics #where_clause {
+            const ALIGN: usize = {
+                let align = #min_align;
+                #(let align = #layout::max(align, #fields::ALIGN);)*
+                align
+            };
+            const SIZE: usize = {
+                let offset = 0;
+                #(
+                    let offset = #layout::align_to(offset, #fields::ALIGN)
+                        + #fields::SIZE;
+                )*
+                #layout::align_to(offset, <Self as #layout::#trait_name>::ALIGN)
+            };
+
+            fn #write(&self, bytes: &mut [u8]) {
+                let offset = 0;
+                #(
+                    let offset = #layout::align_to(offset, #fields::ALIGN);
+                    let end = offset + #fields::SIZE;
+                    #fields::#write(&self.#members, &mut bytes[offset..end]);
+                    let offset = end;
+                )*
+                let _ = offset;
+            }
+        }
+    })
+}
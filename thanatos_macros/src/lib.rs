@@ -1,6 +1,48 @@
+mod archetype;
+mod layout;
+mod system;
+mod vertex;
+
 use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, ItemFn};
+
+#[proc_macro_derive(Archetype, attributes(archetype))]
+pub fn derive_archetype(input: TokenStream) -> TokenStream {
+    archetype::derive(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[proc_macro_derive(Std140, attributes(std140))]
+pub fn derive_std140(input: TokenStream) -> TokenStream {
+    layout::derive(
+        parse_macro_input!(input as DeriveInput),
+        layout::Kind::Std140,
+    )
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+#[proc_macro_derive(Std430, attributes(std430))]
+pub fn derive_std430(input: TokenStream) -> TokenStream {
+    layout::derive(
+        parse_macro_input!(input as DeriveInput),
+        layout::Kind::Std430,
+    )
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+#[proc_macro_attribute]
+pub fn system(attr: TokenStream, item: TokenStream) -> TokenStream {
+    system::expand(attr.into(), parse_macro_input!(item as ItemFn))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
 
-#[proc_macro_derive(Archetype)]
-pub fn derive_answer_fn(input: TokenStream) -> TokenStream {
-    format!("impl_archetype!({});", input).parse().unwrap()
+#[proc_macro_derive(VertexLayout, attributes(vertex, location))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    vertex::derive(parse_macro_input!(input as DeriveInput))
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
@@ -0,0 +1,345 @@
+use proc_macro2::{TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use syn::{
+    meta::ParseNestedMeta, parse::Parser, parse_quote, punctuated::Punctuated, visit::Visit, Error,
+    Expr, ExprMethodCall, ExprPath, FnArg, GenericArgument, Ident, ItemFn, Macro, Pat, Path,
+    Result, ReturnType, Token, Type,
+};
+
+#[derive(Default)]
+struct Args {
+    krate: Option<Path>,
+    events: Vec<Pat>,
+    reads: Vec<Type>,
+    writes: Vec<Type>,
+}
+
+fn parse_types(meta: &ParseNestedMeta) -> Result<Vec<Type>> {
+    let content;
+    syn::parenthesized!(content in meta.input);
+    Ok(Punctuated::<Type, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect())
+}
+
+fn parse_args(attr: TokenStream) -> Result<Args> {
+    let mut args = Args::default();
+    let parser = syn::meta::parser(|meta| {
+        if meta.path.is_ident("events") {
+            let content;
+            syn::parenthesized!(content in meta.input);
+            args.events = Punctuated::<Pat, Token![,]>::parse_terminated_with(
+                &content,
+                Pat::parse_multi_with_leading_vert,
+            )?
+            .into_iter()
+            .collect();
+            Ok(())
+        } else if meta.path.is_ident("reads") {
+            args.reads.extend(parse_types(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("writes") {
+            args.writes.extend(parse_types(&meta)?);
+            Ok(())
+        } else if meta.path.is_ident("crate") {
+            args.krate = Some(meta.value()?.parse::<syn::LitStr>()?.parse()?);
+            Ok(())
+        } else {
+            Err(meta
+                .error("unknown system argument, expected `events`, `reads`, `writes` or `crate`"))
+        }
+    });
+    parser.parse2(attr)?;
+    Ok(args)
+}
+
+struct Param {
+    pat: Pat,
+    ty: Type,
+}
+
+fn parse_param(arg: &FnArg, mutable: bool) -> Result<Param> {
+    let FnArg::Typed(arg) = arg else {
+        return Err(Error::new_spanned(arg, "systems can't take self"));
+    };
+    match &*arg.ty {
+        Type::Reference(reference) if reference.mutability.is_some() == mutable => Ok(Param {
+            pat: (*arg.pat).clone(),
+            ty: (*reference.elem).clone(),
+        }),
+        ty if mutable => Err(Error::new_spanned(ty, "expected `&mut World`")),
+        ty => Err(Error::new_spanned(
+            ty,
+            "expected a reference to the event type",
+        )),
+    }
+}
+
+#[derive(Default)]
+struct Borrows {
+    resource_reads: Vec<Type>,
+    resource_writes: Vec<Type>,
+    component_reads: Vec<Type>,
+    component_writes: Vec<Type>,
+    archetype_reads: Vec<Type>,
+    archetype_writes: Vec<Type>,
+}
+
+impl Borrows {
+    fn query(&mut self, ty: &Type) {
+        match ty {
+            Type::Reference(reference) if reference.mutability.is_some() => {
+                self.component_writes.push((*reference.elem).clone())
+            }
+            Type::Reference(reference) => self.component_reads.push((*reference.elem).clone()),
+            Type::Tuple(tuple) => tuple.elems.iter().for_each(|ty| self.query(ty)),
+            Type::Paren(paren) => self.query(&paren.elem),
+            // With/Without/Is only filter tables, and anything else isn't a query we know
+            _ => (),
+        }
+    }
+}
+
+/// Finds the resources and components a system borrows from the calls it
+/// makes on its world, e.g. `world.get_mut::<Camera>()`. Any other use of
+/// the world, like passing it to a function or macro or calling `submit` or
+/// `spawn` on it, could touch anything, so it `escapes`.
+struct Scanner<'a> {
+    world: &'a Ident,
+    borrows: Borrows,
+    escapes: bool,
+}
+
+impl Scanner<'_> {
+    fn borrow(&mut self, call: &ExprMethodCall) -> bool {
+        let Expr::Path(receiver) = &*call.receiver else {
+            return false;
+        };
+        if !receiver.path.is_ident(self.world) {
+            return false;
+        }
+        let Some(GenericArgument::Type(ty)) = call
+            .turbofish
+            .as_ref()
+            .and_then(|turbofish| turbofish.args.first())
+        else {
+            return false;
+        };
+
+        let ty = ty.clone();
+        match call.method.to_string().as_str() {
+            "get" => self.borrows.resource_reads.push(ty),
+            "get_mut" | "remove" => self.borrows.resource_writes.push(ty),
+            "query" => self.borrows.query(&ty),
+            "iter_archetype" => self.borrows.archetype_reads.push(ty),
+            "iter_archetype_mut" => self.borrows.archetype_writes.push(ty),
+            _ => return false,
+        }
+        true
+    }
+}
+
+impl<'ast> Visit<'ast> for Scanner<'_> {
+    fn visit_expr_method_call(&mut self, call: &'ast ExprMethodCall) {
+        if self.borrow(call) {
+            // The receiver is the world, which mustn't count as escaping
+            call.args.iter().for_each(|arg| self.visit_expr(arg));
+        } else {
+            syn::visit::visit_expr_method_call(self, call);
+        }
+    }
+
+    fn visit_expr_path(&mut self, path: &'ast ExprPath) {
+        if path.path.is_ident(self.world) {
+            self.escapes = true;
+        }
+    }
+
+    fn visit_macro(&mut self, mac: &'ast Macro) {
+        // syn leaves macro bodies as tokens, so read them as arguments like
+        // `println!`'s, and failing that any mention of the world escapes
+        match mac.parse_body_with(Punctuated::<Expr, Token![,]>::parse_terminated) {
+            Ok(args) => args.iter().for_each(|arg| self.visit_expr(arg)),
+            Err(_) => self.escapes |= mentions(mac.tokens.clone(), self.world),
+        }
+    }
+}
+
+fn mentions(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(token) => token == *ident,
+        TokenTree::Group(group) => mentions(group.stream(), ident),
+        _ => false,
+    })
+}
+
+fn dedup(types: &mut Vec<Type>) {
+    let mut seen = Vec::new();
+    types.retain(|ty| {
+        let key = ty.to_token_stream().to_string();
+        let new = !seen.contains(&key);
+        seen.push(key);
+        new
+    });
+}
+
+pub fn expand(attr: TokenStream, item: ItemFn) -> Result<TokenStream> {
+    let args = parse_args(attr)?;
+    let krate = args.krate.unwrap_or_else(|| parse_quote!(::tecs));
+
+    let sig = &item.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(Error::new_spanned(
+            asyncness,
+            "systems can't be async, spawn onto `Tasks` instead",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(Error::new_spanned(
+            &sig.generics,
+            "systems can't be generic",
+        ));
+    }
+    if let ReturnType::Type(_, ty) = &sig.output {
+        return Err(Error::new_spanned(ty, "systems can't return anything"));
+    }
+
+    let mut inputs = sig.inputs.iter();
+    let world = inputs
+        .next()
+        .ok_or_else(|| {
+            Error::new_spanned(
+                &sig.inputs,
+                "systems take `&mut World` as their first argument",
+            )
+        })
+        .and_then(|arg| parse_param(arg, true))?;
+    let Pat::Ident(world_ident) = &world.pat else {
+        return Err(Error::new_spanned(&world.pat, "expected an identifier"));
+    };
+    let world_ident = &world_ident.ident;
+    let event = inputs
+        .next()
+        .map(|arg| parse_param(arg, false))
+        .transpose()?;
+    if let Some(extra) = inputs.next() {
+        return Err(Error::new_spanned(
+            extra,
+            "systems take at most a world and an event",
+        ));
+    }
+    if event.is_none() && !args.events.is_empty() {
+        return Err(Error::new_spanned(
+            &args.events[0],
+            "only systems taking an event can filter events",
+        ));
+    }
+
+    let mut scanner = Scanner {
+        world: world_ident,
+        borrows: Borrows::default(),
+        escapes: false,
+    };
+    scanner.visit_block(&item.block);
+    // Declared reads and writes only name resources, so they can't stand in
+    // for whatever an escaped world gets up to
+    let access = match scanner.escapes {
+        true => quote!(#krate::Access::exclusive()),
+        false => quote!(#krate::Access::default()),
+    };
+    let mut borrows = scanner.borrows;
+    borrows.resource_reads.extend(args.reads);
+    borrows.resource_writes.extend(args.writes);
+    [
+        &mut borrows.resource_reads,
+        &mut borrows.resource_writes,
+        &mut borrows.component_reads,
+        &mut borrows.component_writes,
+        &mut borrows.archetype_reads,
+        &mut borrows.archetype_writes,
+    ]
+    .into_iter()
+    .for_each(dedup);
+    let Borrows {
+        resource_reads,
+        resource_writes,
+        component_reads,
+        component_writes,
+        archetype_reads,
+        archetype_writes,
+    } = borrows;
+
+    let name = &sig.ident;
+    let vis = &item.vis;
+    let (docs, attrs): (Vec<_>, Vec<_>) = item
+        .attrs
+        .iter()
+        .partition(|attr| attr.path().is_ident("doc"));
+    let block = &item.block;
+    let world_ty = &world.ty;
+    let event_ty = quote!(<#world_ty as #krate::EventType>::Event);
+
+    let (run, event, tick) = match &event {
+        Some(Param { pat, ty }) => {
+            let filter = if args.events.is_empty() {
+                quote!(true)
+            } else {
+                let events = &args.events;
+                quote!(::std::matches!(event, #(#events)|*))
+            };
+            (
+                quote! {
+                    #(#attrs)*
+                    #vis fn run(#world_ident: &mut #world_ty, #pat: &#ty) #block
+                },
+                quote! {
+                    if #filter {
+                        Self::run(world, event)
+                    }
+                },
+                quote!(),
+            )
+        }
+        None => (
+            quote! {
+                #(#attrs)*
+                #vis fn run(#world_ident: &mut #world_ty) #block
+            },
+            quote!(),
+            quote!(Self::run(world)),
+        ),
+    };
+
+    Ok(quote! {
+        #(#docs)*
+        #[allow(non_camel_case_types)]
+        #vis struct #name;
+
+        impl #name {
+            #run
+        }
+
+        impl #krate::System<#event_ty> for #name {
+            #[allow(unused_variables)]
+            fn event(&self, world: &mut #world_ty, event: &#event_ty) {
+                #event
+            }
+
+            #[allow(unused_variables)]
+            fn tick(&self, world: &mut #world_ty) {
+                #tick
+            }
+
+            fn access(&self) -> #krate::Access {
+                let mut access = #access;
+                #(access.resources.reads.push(::std::any::TypeId::of::<#resource_reads>());)*
+                #(access.resources.writes.push(::std::any::TypeId::of::<#resource_writes>());)*
+                #(access.components.reads.push(::std::any::TypeId::of::<#component_reads>());)*
+                #(access.components.writes.push(::std::any::TypeId::of::<#component_writes>());)*
+                #(access.components.reads.extend(<#archetype_reads as #krate::Archetype>::columns());)*
+                #(access.components.writes.extend(<#archetype_writes as #krate::Archetype>::columns());)*
+                access
+            }
+        }
+    })
+}
@@ -0,0 +1,84 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse_quote, Attribute, Data, DeriveInput, Error, LitInt, Member, Path, Result};
+
+fn parse_crate(attrs: &[Attribute]) -> Result<Path> {
+    let mut krate = parse_quote!(::hephaestus);
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("vertex")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("crate") {
+                krate = meta.value()?.parse::<syn::LitStr>()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("unknown vertex attribute, expected `crate = \"...\"`"))
+            }
+        })?;
+    }
+    Ok(krate)
+}
+
+fn parse_location(attrs: &[Attribute]) -> Result<Option<u32>> {
+    let mut location = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("location")) {
+        if location.is_some() {
+            return Err(Error::new_spanned(attr, "location is given more than once"));
+        }
+        location = Some(attr.parse_args::<LitInt>()?.base10_parse()?);
+    }
+    Ok(location)
+}
+
+pub fn derive(input: DeriveInput) -> Result<TokenStream> {
+    let krate = parse_crate(&input.attrs)?;
+    let Data::Struct(data) = &input.data else {
+        return Err(Error::new_spanned(
+            &input.ident,
+            "VertexLayout can only be derived for structs",
+        ));
+    };
+
+    let mut used: Vec<(u32, Member)> = Vec::new();
+    let mut next = 0;
+    let mut attributes = Vec::new();
+    for (i, field) in data.fields.iter().enumerate() {
+        let member = field
+            .ident
+            .clone()
+            .map(Member::Named)
+            .unwrap_or_else(|| Member::Unnamed(i.into()));
+        let location = parse_location(&field.attrs)?.unwrap_or(next);
+        if let Some((_, other)) = used.iter().find(|(used, _)| *used == location) {
+            let other = match other {
+                Member::Named(ident) => ident.to_string(),
+                Member::Unnamed(index) => index.index.to_string(),
+            };
+            return Err(Error::new_spanned(
+                field,
+                format!("location {location} is already used by `{other}`"),
+            ));
+        }
+        used.push((location, member.clone()));
+        next = location + 1;
+
+        let ty = &field.ty;
+        attributes.push(quote! {
+            .attribute_at(
+                #location,
+                <#ty as #krate::vertex::Attribute>::TYPE,
+                ::std::mem::offset_of!(Self, #member),
+            )
+        });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics #krate::vertex::Layout for #name #ty_generics #where_clause {
+            fn info() -> #krate::vertex::Info {
+                #krate::vertex::Info::new(::std::mem::size_of::<Self>())
+                    #(#attributes)*
+            }
+        }
+    })
+}
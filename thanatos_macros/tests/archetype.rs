@@ -0,0 +1,6 @@
+#[test]
+fn archetype() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/archetype/pass/*.rs");
+    t.compile_fail("tests/archetype/fail/*.rs");
+}
@@ -0,0 +1,9 @@
+use thanatos_macros::Archetype;
+
+#[derive(Archetype)]
+struct Twice {
+    first: u32,
+    second: u32,
+}
+
+fn main() {}
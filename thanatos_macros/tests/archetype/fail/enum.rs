@@ -0,0 +1,9 @@
+use thanatos_macros::Archetype;
+
+#[derive(Archetype)]
+enum NotAStruct {
+    A,
+    B,
+}
+
+fn main() {}
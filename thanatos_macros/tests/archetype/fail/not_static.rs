@@ -0,0 +1,8 @@
+use thanatos_macros::Archetype;
+
+#[derive(Archetype)]
+struct Borrowed<'a> {
+    value: &'a u32,
+}
+
+fn main() {}
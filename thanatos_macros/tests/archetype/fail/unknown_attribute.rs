@@ -0,0 +1,9 @@
+use thanatos_macros::Archetype;
+
+#[derive(Archetype)]
+struct Unknown {
+    #[archetype(rename = "other")]
+    value: u32,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use glam::Vec3;
+use tecs::World;
+use thanatos_macros::Archetype;
+
+// The same type named two ways gets past the derive, but not the world
+#[derive(Archetype)]
+struct Twice {
+    first: Vec3,
+    second: glam::Vec3,
+}
+
+fn main() {
+    let result = std::panic::catch_unwind(|| {
+        World::<()>::new().spawn(Twice {
+            first: Vec3::X,
+            second: Vec3::Y,
+        });
+    });
+    assert!(result.is_err());
+}
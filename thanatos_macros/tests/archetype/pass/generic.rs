@@ -0,0 +1,27 @@
+use std::marker::PhantomData;
+
+use tecs::World;
+use thanatos_macros::Archetype;
+
+struct Tag<T>(PhantomData<T>);
+
+#[derive(Archetype)]
+struct Tagged<T, U> {
+    value: T,
+    #[archetype(skip)]
+    _unused: PhantomData<U>,
+    tag: Tag<U>,
+}
+
+fn main() {
+    let mut world = World::<()>::new();
+    world.spawn(Tagged::<u32, ()> {
+        value: 5,
+        _unused: PhantomData,
+        tag: Tag(PhantomData),
+    });
+
+    let values = world.query::<&u32>();
+    assert_eq!(*values.iter().next().unwrap(), 5);
+    assert!(world.query::<&PhantomData<()>>().iter().next().is_none());
+}
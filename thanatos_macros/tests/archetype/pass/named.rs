@@ -0,0 +1,31 @@
+use tecs::World;
+use thanatos_macros::Archetype;
+
+pub struct Health(u32);
+pub struct Name(&'static str);
+
+/// A documented archetype with public fields
+#[derive(Archetype)]
+#[allow(dead_code)]
+pub struct Player {
+    /// Hit points
+    pub health: Health,
+    #[allow(unused)]
+    pub(crate) name: Name,
+}
+
+fn main() {
+    let mut world = World::<()>::new();
+    world.spawn(Player {
+        health: Health(10),
+        name: Name("player"),
+    });
+    world.spawn(Player {
+        health: Health(20),
+        name: Name("other"),
+    });
+
+    let (health, name) = world.query::<(&Health, &Name)>();
+    assert_eq!(health.iter().map(|health| health.0).sum::<u32>(), 30);
+    assert_eq!(name.iter().next().unwrap().0, "player");
+}
@@ -0,0 +1,14 @@
+mod ecs {
+    pub use tecs::*;
+}
+
+#[derive(thanatos_macros::Archetype)]
+#[archetype(crate = "ecs")]
+struct Renamed {
+    value: u8,
+}
+
+fn main() {
+    let mut world = ecs::World::<()>::new();
+    world.spawn(Renamed { value: 1 });
+}
@@ -0,0 +1,28 @@
+use tecs::World;
+use thanatos_macros::Archetype;
+
+mod physics {
+    pub struct Position(pub f32);
+}
+
+mod render {
+    pub struct Position(pub f32);
+}
+
+#[derive(Archetype)]
+struct Body {
+    simulated: physics::Position,
+    drawn: render::Position,
+}
+
+fn main() {
+    let mut world = World::<()>::new();
+    world.spawn(Body {
+        simulated: physics::Position(1.0),
+        drawn: render::Position(2.0),
+    });
+
+    let (simulated, drawn) = world.query::<(&physics::Position, &render::Position)>();
+    assert_eq!(simulated.iter().next().unwrap().0, 1.0);
+    assert_eq!(drawn.iter().next().unwrap().0, 2.0);
+}
@@ -0,0 +1,21 @@
+use tecs::World;
+use thanatos_macros::Archetype;
+
+struct Position(f32);
+struct Velocity(f32);
+
+#[derive(Archetype)]
+struct Particle(Position, Velocity);
+
+#[derive(Archetype)]
+struct Marker;
+
+fn main() {
+    let mut world = World::<()>::new();
+    world.spawn(Particle(Position(1.0), Velocity(2.0)));
+    world.spawn(Marker);
+
+    let (position, velocity) = world.query::<(&Position, &Velocity)>();
+    assert_eq!(position.iter().next().unwrap().0, 1.0);
+    assert_eq!(velocity.iter().next().unwrap().0, 2.0);
+}
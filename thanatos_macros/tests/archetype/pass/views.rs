@@ -0,0 +1,64 @@
+use std::marker::PhantomData;
+
+use tecs::World;
+use thanatos_macros::Archetype;
+
+#[derive(Debug, PartialEq)]
+pub struct Health(u32);
+pub struct Name(&'static str);
+
+#[derive(Archetype)]
+pub struct Player {
+    pub health: Health,
+    pub name: Name,
+}
+
+#[derive(Archetype)]
+struct Pair(Health, #[archetype(skip)] (), Name);
+
+#[derive(Archetype)]
+struct Unit;
+
+#[derive(Archetype)]
+struct Phantom<T> {
+    value: u8,
+    #[archetype(skip)]
+    _marker: PhantomData<T>,
+}
+
+fn main() {
+    let mut world = World::<()>::new();
+    world.spawn(Player {
+        health: Health(10),
+        name: Name("first"),
+    });
+    world.spawn(Player {
+        health: Health(20),
+        name: Name("second"),
+    });
+    world.spawn(Pair(Health(30), (), Name("pair")));
+    world.spawn(Unit);
+    world.spawn(Unit);
+    world.spawn(Phantom::<String> {
+        value: 1,
+        _marker: PhantomData,
+    });
+
+    for mut player in world.iter_archetype_mut::<Player>() {
+        player.health.0 += 1;
+    }
+
+    let players: Vec<PlayerRef> = world.iter_archetype::<Player>().collect();
+    assert_eq!(players.len(), 2);
+    assert_eq!(*players[0].health, Health(11));
+    assert_eq!(players[1].name.0, "second");
+    drop(players);
+
+    let pair = world.iter_archetype::<Pair>().next().unwrap();
+    assert_eq!(pair.0 .0, 30);
+    assert_eq!(pair.1 .0, "pair");
+
+    assert_eq!(world.iter_archetype::<Unit>().count(), 2);
+    assert_eq!(*world.iter_archetype_mut::<Phantom<String>>().next().unwrap().value, 1);
+    assert_eq!(world.iter_archetype::<Phantom<u32>>().count(), 0);
+}
@@ -0,0 +1,6 @@
+#[test]
+fn layout() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/layout/pass/*.rs");
+    t.compile_fail("tests/layout/fail/*.rs");
+}
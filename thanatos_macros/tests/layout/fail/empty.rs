@@ -0,0 +1,6 @@
+use thanatos_macros::Std430;
+
+#[derive(Std430)]
+struct Empty {}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use thanatos_macros::Std140;
+
+#[derive(Std140)]
+struct Block {
+    scale: f32,
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,63 @@
+use glam::{Mat3, Vec2, Vec3};
+use hephaestus::layout::{Std140, Std430};
+use thanatos_macros::{Std140, Std430};
+
+#[derive(Std140, Std430)]
+struct Light {
+    position: Vec3,
+    intensity: f32,
+    colour: Vec3,
+}
+
+#[derive(Std140, Std430)]
+struct Block {
+    light: Light,
+    lights: [f32; 2],
+    transform: Mat3,
+    enabled: bool,
+    scale: Vec2,
+}
+
+fn read(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_ne_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn main() {
+    let block = Block {
+        light: Light {
+            position: Vec3::new(1.0, 2.0, 3.0),
+            intensity: 4.0,
+            colour: Vec3::new(5.0, 6.0, 7.0),
+        },
+        lights: [8.0, 9.0],
+        transform: Mat3::from_cols(Vec3::splat(10.0), Vec3::splat(11.0), Vec3::splat(12.0)),
+        enabled: true,
+        scale: Vec2::new(13.0, 14.0),
+    };
+
+    assert_eq!(<Light as Std140>::SIZE, 32);
+    assert_eq!(<Block as Std140>::SIZE, 128);
+    let bytes = block.as_std140();
+    assert_eq!(bytes.len(), 128);
+    assert_eq!(read(&bytes, 8), 3.0);
+    assert_eq!(read(&bytes, 12), 4.0);
+    assert_eq!(read(&bytes, 16), 5.0);
+    assert_eq!(read(&bytes, 32), 8.0);
+    assert_eq!(read(&bytes, 48), 9.0);
+    assert_eq!(read(&bytes, 64), 10.0);
+    assert_eq!(read(&bytes, 80), 11.0);
+    assert_eq!(read(&bytes, 104), 12.0);
+    assert_eq!(u32::from_ne_bytes(bytes[112..116].try_into().unwrap()), 1);
+    assert_eq!(read(&bytes, 120), 13.0);
+    assert_eq!(read(&bytes, 124), 14.0);
+
+    assert_eq!(<Light as Std430>::SIZE, 32);
+    assert_eq!(<Block as Std430>::SIZE, 112);
+    let bytes = block.as_std430();
+    assert_eq!(read(&bytes, 32), 8.0);
+    assert_eq!(read(&bytes, 36), 9.0);
+    assert_eq!(read(&bytes, 48), 10.0);
+    assert_eq!(read(&bytes, 64), 11.0);
+    assert_eq!(u32::from_ne_bytes(bytes[96..100].try_into().unwrap()), 1);
+    assert_eq!(read(&bytes, 104), 13.0);
+}
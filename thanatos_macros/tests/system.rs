@@ -0,0 +1,6 @@
+#[test]
+fn system() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/system/pass/*.rs");
+    t.compile_fail("tests/system/fail/*.rs");
+}
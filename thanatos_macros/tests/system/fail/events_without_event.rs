@@ -0,0 +1,10 @@
+use thanatos_macros::system;
+
+enum Event {
+    Stop,
+}
+
+#[system(events(Event::Stop))]
+fn stop(world: &mut tecs::World<Event>) {}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use thanatos_macros::system;
+
+#[system]
+fn tick(world: &mut tecs::World<()>) -> bool {
+    true
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use thanatos_macros::system;
+
+#[system]
+fn tick(world: &tecs::World<()>) {}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use thanatos_macros::system;
+
+#[system(before(other))]
+fn tick(world: &mut tecs::World<()>) {}
+
+fn main() {}
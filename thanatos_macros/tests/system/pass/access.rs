@@ -0,0 +1,50 @@
+use std::any::TypeId;
+
+use tecs::{Archetype, System, World};
+use thanatos_macros::{system, Archetype};
+
+struct Position(f32);
+struct Velocity(f32);
+struct Gravity(f32);
+struct Log(Vec<String>);
+
+#[derive(Archetype)]
+struct Body {
+    position: Position,
+    velocity: Velocity,
+}
+
+fn log(world: &World<()>, message: &str) {
+    world.get_mut::<Log>().unwrap().0.push(message.to_owned());
+}
+
+#[system(writes(Log))]
+fn physics(world: &mut World<()>) {
+    let gravity = world.get::<Gravity>().unwrap().0;
+    for mut body in world.iter_archetype_mut::<Body>() {
+        body.velocity.0 -= gravity;
+        body.position.0 += body.velocity.0;
+    }
+    log(world, "physics");
+}
+
+#[system]
+fn report(world: &mut World<()>) {
+    let (positions, velocities) = world.query::<(&Position, &Velocity)>();
+    let _ = (positions, velocities);
+}
+
+fn main() {
+    let access = physics.access();
+    assert_eq!(access.resources.reads, vec![TypeId::of::<Gravity>()]);
+    assert_eq!(access.resources.writes, vec![TypeId::of::<Log>()]);
+    assert_eq!(access.components.writes, Body::columns());
+
+    let access = report.access();
+    assert_eq!(
+        access.components.reads,
+        vec![TypeId::of::<Position>(), TypeId::of::<Velocity>()]
+    );
+    assert!(access.components.writes.is_empty());
+    assert!(access.conflicts(&physics.access()));
+}
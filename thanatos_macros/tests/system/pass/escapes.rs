@@ -0,0 +1,117 @@
+use std::any::TypeId;
+
+use tecs::{System, World};
+use thanatos_macros::{system, Archetype};
+
+struct Position(f32);
+struct Log(Vec<String>);
+struct Camera;
+
+#[derive(Archetype)]
+struct Particle {
+    position: Position,
+}
+
+fn log(world: &World<()>, message: &str) {
+    world.get_mut::<Log>().unwrap().0.push(message.to_owned());
+}
+
+macro_rules! log {
+    ($world:ident) => {
+        log($world, "macro")
+    };
+    (to $world:ident) => {
+        log($world, "macro")
+    };
+}
+
+// The scanner can't see into `log`, so this has to be exclusive
+#[system]
+fn helper(world: &mut World<()>) {
+    let _ = world.query::<&Position>();
+    log(world, "helper");
+}
+
+#[system]
+fn spawner(world: &mut World<()>) {
+    world.spawn(Particle {
+        position: Position(0.0),
+    });
+}
+
+#[system]
+fn reborrow(world: &mut World<()>) {
+    let world = &mut *world;
+    let _ = world.get::<Log>();
+}
+
+// Declared access only adds to an escaped world, as it can't name components
+#[system(writes(Log))]
+fn declared(world: &mut World<()>) {
+    log(world, "declared");
+}
+
+#[system(reads(Camera))]
+fn declared_spawner(world: &mut World<()>) {
+    world.spawn(Particle {
+        position: Position(0.0),
+    });
+}
+
+// Macro arguments are scanned like any other expression
+#[system]
+fn printed(world: &mut World<()>) {
+    println!("{}", world.get::<Log>().is_some());
+}
+
+#[system]
+fn in_macro(world: &mut World<()>) {
+    log!(world);
+}
+
+// Bodies that aren't expressions escape if they mention the world at all
+#[system]
+fn in_tokens(world: &mut World<()>) {
+    log!(to world);
+}
+
+#[system]
+fn in_block_macro(world: &mut World<()>) {
+    thread_local! {
+        static SEEN: bool = false;
+    }
+    let _ = vec![world.query::<&Position>()];
+    log! { world };
+}
+
+#[system]
+fn visible(world: &mut World<()>) {
+    let message = world.get::<Log>().unwrap().0.len().to_string();
+    world.get_mut::<Log>().unwrap().0.push(message);
+}
+
+fn main() {
+    let access = helper.access();
+    assert!(access.exclusive);
+    assert_eq!(access.components.reads, vec![TypeId::of::<Position>()]);
+    assert!(spawner.access().exclusive);
+    assert!(reborrow.access().exclusive);
+
+    let access = declared.access();
+    assert!(access.exclusive);
+    assert_eq!(access.resources.writes, vec![TypeId::of::<Log>()]);
+    let access = declared_spawner.access();
+    assert!(access.exclusive);
+    assert_eq!(access.resources.reads, vec![TypeId::of::<Camera>()]);
+
+    let access = printed.access();
+    assert!(!access.exclusive);
+    assert_eq!(access.resources.reads, vec![TypeId::of::<Log>()]);
+    assert!(in_macro.access().exclusive);
+    assert!(in_tokens.access().exclusive);
+    let access = in_block_macro.access();
+    assert!(access.exclusive);
+    assert_eq!(access.components.reads, vec![TypeId::of::<Position>()]);
+    assert!(!visible.access().exclusive);
+    assert!(helper.access().conflicts(&visible.access()));
+}
@@ -0,0 +1,67 @@
+use std::any::TypeId;
+
+use tecs::{System, World};
+use thanatos_macros::system;
+
+type Game = World<Event>;
+
+enum Event {
+    Resized(u32),
+    Moved,
+    Stop,
+}
+
+#[derive(Default)]
+struct Size(u32);
+
+#[derive(Default)]
+struct Count(u32);
+
+/// Keeps `Size` up to date.
+#[system(events(Event::Resized(_)))]
+fn resize(world: &mut Game, event: &Event) {
+    if let Event::Resized(size) = event {
+        world.get_mut::<Size>().unwrap().0 = *size;
+    }
+}
+
+#[system(events(Event::Moved | Event::Stop))]
+fn count(world: &mut Game, _: &Event) {
+    world.get_mut::<Count>().unwrap().0 += 1;
+}
+
+#[system]
+fn count_ticks(world: &mut Game) {
+    let size = world.get::<Size>().unwrap().0;
+    world.get_mut::<Count>().unwrap().0 += size;
+}
+
+fn main() {
+    let mut world = Game::new()
+        .with_resource(Size::default())
+        .with_resource(Count::default())
+        .with_system(resize)
+        .with_system(count)
+        .with_system(count_ticks);
+
+    world.submit(Event::Resized(10));
+    world.submit(Event::Moved);
+    world.submit(Event::Stop);
+    world.tick();
+    assert_eq!(world.get::<Size>().unwrap().0, 10);
+    assert_eq!(world.get::<Count>().unwrap().0, 12);
+
+    let mut direct = Game::new().with_resource(Size::default());
+    resize::run(&mut direct, &Event::Resized(3));
+    assert_eq!(direct.get::<Size>().unwrap().0, 3);
+
+    let access = resize.access();
+    assert!(!access.exclusive);
+    assert_eq!(access.resources.writes, vec![TypeId::of::<Size>()]);
+
+    let access = count_ticks.access();
+    assert_eq!(access.resources.reads, vec![TypeId::of::<Size>()]);
+    assert_eq!(access.resources.writes, vec![TypeId::of::<Count>()]);
+    assert!(access.conflicts(&count.access()));
+    assert!(!resize.access().conflicts(&count.access()));
+}
@@ -0,0 +1,6 @@
+#[test]
+fn vertex() {
+    let t = trybuild::TestCases::new();
+    t.pass("tests/vertex/pass/*.rs");
+    t.compile_fail("tests/vertex/fail/*.rs");
+}
@@ -0,0 +1,10 @@
+use thanatos_macros::VertexLayout;
+
+#[derive(VertexLayout)]
+struct Vertex {
+    position: [f32; 3],
+    #[location(0)]
+    normal: [f32; 3],
+}
+
+fn main() {}
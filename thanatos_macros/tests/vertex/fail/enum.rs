@@ -0,0 +1,8 @@
+use thanatos_macros::VertexLayout;
+
+#[derive(VertexLayout)]
+enum Vertex {
+    Position([f32; 3]),
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use thanatos_macros::VertexLayout;
+
+#[derive(VertexLayout)]
+struct Vertex {
+    position: [f32; 3],
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,63 @@
+use std::mem::{offset_of, size_of};
+
+use glam::{Vec2, Vec3};
+use hephaestus::vertex::{AttributeType, Layout, Normalized, Scalar};
+use thanatos_macros::VertexLayout;
+
+#[repr(C)]
+#[derive(VertexLayout)]
+struct Vertex {
+    position: Vec3,
+    normal: Vec3,
+    uv: Vec2,
+    colour: Normalized<[u8; 4]>,
+    joints: [u16; 4],
+}
+
+#[repr(C)]
+#[derive(VertexLayout)]
+struct Sparse {
+    #[location(2)]
+    weight: f32,
+    #[location(0)]
+    position: [f32; 3],
+    normal: Normalized<[i8; 3]>,
+}
+
+#[repr(C)]
+#[derive(VertexLayout)]
+struct Tuple(Vec3, u32);
+
+fn main() {
+    let info = Vertex::info();
+    assert_eq!(info.stride, size_of::<Vertex>());
+    assert_eq!(
+        info.attributes,
+        vec![
+            (0, AttributeType::Vec3, 0),
+            (1, AttributeType::Vec3, offset_of!(Vertex, normal)),
+            (2, AttributeType::Vec2, offset_of!(Vertex, uv)),
+            (3, AttributeType::Normalized(Scalar::U8, 4), offset_of!(Vertex, colour)),
+            (4, AttributeType::Integer(Scalar::U16, 4), offset_of!(Vertex, joints)),
+        ]
+    );
+
+    let info = Sparse::info();
+    assert_eq!(
+        info.attributes,
+        vec![
+            (2, AttributeType::Float, 0),
+            (0, AttributeType::Vec3, offset_of!(Sparse, position)),
+            (1, AttributeType::Normalized(Scalar::I8, 3), offset_of!(Sparse, normal)),
+        ]
+    );
+
+    let info = Tuple::info();
+    assert_eq!(
+        info.attributes,
+        vec![
+            (0, AttributeType::Vec3, 0),
+            (1, AttributeType::Integer(Scalar::U32, 1), offset_of!(Tuple, 1)),
+        ]
+    );
+}